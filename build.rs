@@ -2,7 +2,21 @@ use std::env;
 use std::fs;
 use std::path::Path;
 
+/// Sprites packed into the sheet, and the sheet and frame list written to `OUT_DIR`.
+/// Build scripts may only write there, so the game builds them into the executable.
+const SPRITES_DIR: &str = "assets/sprites";
+const SHEET_IMAGE: &str = "sprites.png";
+const SHEET_FRAMES: &str = "sprites.json";
+const SHEET_MAX_WIDTH: u32 = 1024;
+/// Empty pixels between frames so neighbors never bleed into each other
+const PADDING: u32 = 1;
+
 fn main() {
+    pack_sprites();
+    if env::var_os("CARGO_FEATURE_EMBED_ASSETS").is_some() {
+        embed_assets();
+    }
+
     let out_dir = env::var("OUT_DIR").unwrap();
 
     // Create symlink to assets in target directory
@@ -36,6 +50,142 @@ fn main() {
     println!("cargo:rerun-if-changed=assets/");
 }
 
+struct Frame {
+    name: String,
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+    x: u32,
+    y: u32,
+}
+
+/// Packs every PNG in `SPRITES_DIR` into one sheet, in rows from the tallest down
+fn pack_sprites() {
+    println!("cargo:rerun-if-changed={}", SPRITES_DIR);
+
+    let mut frames = Vec::new();
+    for entry in fs::read_dir(SPRITES_DIR).expect("Failed to read the sprites folder") {
+        let path = entry.expect("Failed to read the sprites folder").path();
+        if path.extension().is_none_or(|extension| extension != "png") {
+            continue;
+        }
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let (width, height, rgba) = decode_png(&path);
+        frames.push(Frame { name, width, height, rgba, x: 0, y: 0 });
+    }
+    frames.sort_by(|a, b| b.height.cmp(&a.height).then_with(|| a.name.cmp(&b.name)));
+
+    let (mut x, mut y, mut row_height, mut sheet_width) = (0, 0, 0, 0);
+    for frame in &mut frames {
+        if x > 0 && x + frame.width > SHEET_MAX_WIDTH {
+            x = 0;
+            y += row_height + PADDING;
+            row_height = 0;
+        }
+        frame.x = x;
+        frame.y = y;
+        x += frame.width + PADDING;
+        row_height = row_height.max(frame.height);
+        sheet_width = sheet_width.max(frame.x + frame.width);
+    }
+    let sheet_height = y + row_height;
+
+    let mut sheet = vec![0; (sheet_width * sheet_height * 4) as usize];
+    for frame in &frames {
+        for row in 0..frame.height {
+            let src = (row * frame.width * 4) as usize;
+            let dst = (((frame.y + row) * sheet_width + frame.x) * 4) as usize;
+            let len = (frame.width * 4) as usize;
+            sheet[dst..dst + len].copy_from_slice(&frame.rgba[src..src + len]);
+        }
+    }
+
+    let mut image = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut image, sheet_width, sheet_height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().expect("Failed to encode the sprite sheet");
+        writer.write_image_data(&sheet).expect("Failed to encode the sprite sheet");
+    }
+
+    let entries: Vec<String> = frames
+        .iter()
+        .map(|frame| {
+            format!(
+                "    \"{}\": {{ \"x\": {}, \"y\": {}, \"w\": {}, \"h\": {} }}",
+                frame.name, frame.x, frame.y, frame.width, frame.height
+            )
+        })
+        .collect();
+    let metadata = format!(
+        "{{\n  \"width\": {},\n  \"height\": {},\n  \"frames\": {{\n{}\n  }}\n}}\n",
+        sheet_width,
+        sheet_height,
+        entries.join(",\n")
+    );
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join(SHEET_IMAGE), image).expect("Failed to write the sprite sheet");
+    fs::write(Path::new(&out_dir).join(SHEET_FRAMES), metadata).expect("Failed to write the sprite sheet frames");
+}
+
+/// Width, height and 8-bit RGBA pixels, whatever the file's color type
+fn decode_png(path: &Path) -> (u32, u32, Vec<u8>) {
+    let file = fs::File::open(path).unwrap_or_else(|e| panic!("Failed to open {}: {}", path.display(), e));
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder
+        .read_info()
+        .unwrap_or_else(|e| panic!("Failed to decode {}: {}", path.display(), e));
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .unwrap_or_else(|e| panic!("Failed to decode {}: {}", path.display(), e));
+    buffer.truncate(info.buffer_size());
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer.chunks(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => buffer.chunks(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => buffer.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        png::ColorType::Indexed => panic!("{} is still indexed after expanding", path.display()),
+    };
+    (info.width, info.height, rgba)
+}
+
+/// Writes `embedded_assets.rs` to `OUT_DIR`: every file under `assets/` by path, for `include!`
+fn embed_assets() {
+    let mut files = Vec::new();
+    list_files(Path::new("assets"), &mut files).expect("Failed to list the assets folder");
+    files.sort();
+
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let entries: Vec<String> = files
+        .iter()
+        .map(|file| {
+            let path = file.to_string_lossy().replace('\\', "/");
+            format!("    ({:?}, include_bytes!({:?})),", path, Path::new(&manifest_dir).join(file))
+        })
+        .collect();
+    let source = format!("pub static FILES: &[(&str, &[u8])] = &[\n{}\n];\n", entries.join("\n"));
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("embedded_assets.rs"), source).expect("Failed to write embedded_assets.rs");
+}
+
+fn list_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
 fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
     fs::create_dir_all(dst)?;
     for entry in fs::read_dir(src)? {
@@ -0,0 +1,82 @@
+"use strict";
+
+// Browser helpers for the game, see src/platform.rs for the Rust side.
+// Strings go through wasm memory: Rust passes (ptr, len) in and a buffer (ptr, capacity) out,
+// functions return the full byte length so Rust can retry with a bigger buffer, or -1 for "none".
+
+function flappy_read_string(ptr, len) {
+    return new TextDecoder().decode(new Uint8Array(wasm_memory.buffer, ptr, len));
+}
+
+function flappy_write_string(value, ptr, cap) {
+    const bytes = new TextEncoder().encode(value);
+    if (bytes.length <= cap) {
+        new Uint8Array(wasm_memory.buffer, ptr, bytes.length).set(bytes);
+    }
+    return bytes.length;
+}
+
+miniquad_add_plugin({
+    name: "flappy",
+    version: 3,
+    register_plugin: function (importObject) {
+        importObject.env.flappy_query_param = function (name_ptr, name_len, out_ptr, out_cap) {
+            const name = flappy_read_string(name_ptr, name_len);
+            const value = new URLSearchParams(window.location.search).get(name);
+            if (value === null) {
+                return -1;
+            }
+            return flappy_write_string(value, out_ptr, out_cap);
+        };
+
+        // localStorage throws when disabled (e.g. some private browsing modes)
+        importObject.env.flappy_storage_get = function (key_ptr, key_len, out_ptr, out_cap) {
+            try {
+                const value = window.localStorage.getItem(flappy_read_string(key_ptr, key_len));
+                if (value === null) {
+                    return -1;
+                }
+                return flappy_write_string(value, out_ptr, out_cap);
+            } catch (e) {
+                return -1;
+            }
+        };
+
+        importObject.env.flappy_storage_set = function (key_ptr, key_len, value_ptr, value_len) {
+            try {
+                window.localStorage.setItem(
+                    flappy_read_string(key_ptr, key_len),
+                    flappy_read_string(value_ptr, value_len)
+                );
+                return true;
+            } catch (e) {
+                return false;
+            }
+        };
+
+        // Levels match logging::Level: error, warn, info, debug, trace
+        importObject.env.flappy_console_log = function (level, ptr, len) {
+            const message = flappy_read_string(ptr, len);
+            const log = [console.error, console.warn, console.info, console.debug, console.debug][level] || console.log;
+            log.call(console, message);
+        };
+
+        // Bitmask of the pressed buttons of the first connected gamepad, 0 without one
+        importObject.env.flappy_gamepad_buttons = function () {
+            const pads = navigator.getGamepads ? navigator.getGamepads() : [];
+            for (const pad of pads) {
+                if (!pad || !pad.connected) {
+                    continue;
+                }
+                let mask = 0;
+                pad.buttons.forEach(function (button, index) {
+                    if (button.pressed && index < 32) {
+                        mask |= 1 << index;
+                    }
+                });
+                return mask >>> 0;
+            }
+            return 0;
+        };
+    },
+});
@@ -0,0 +1,69 @@
+/// Steps through a sequence of frame indices at a fixed rate.
+/// It only tracks which frame to show, renderers map the index to a texture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteAnimation {
+    sequence: Vec<usize>,
+    frames_per_second: f32,
+    position: usize,
+    timer: f32,
+    boost_multiplier: f32,
+    boost_remaining: f32,
+    frozen: bool,
+}
+
+impl SpriteAnimation {
+    /// `sequence` lists the frames in play order, e.g. `[0, 1, 2, 1]` to ping-pong over three frames
+    pub fn new(sequence: &[usize], frames_per_second: f32) -> Self {
+        assert!(!sequence.is_empty(), "An animation needs at least one frame");
+        SpriteAnimation {
+            sequence: sequence.to_vec(),
+            frames_per_second,
+            position: 0,
+            timer: 0.0,
+            boost_multiplier: 1.0,
+            boost_remaining: 0.0,
+            frozen: false,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if self.frozen {
+            return;
+        }
+
+        let multiplier = if self.boost_remaining > 0.0 {
+            self.boost_remaining -= dt;
+            self.boost_multiplier
+        } else {
+            1.0
+        };
+
+        self.timer += dt * self.frames_per_second * multiplier;
+        while self.timer >= 1.0 {
+            self.timer -= 1.0;
+            self.position = (self.position + 1) % self.sequence.len();
+        }
+    }
+
+    /// Plays `multiplier` times faster for the next `duration` seconds
+    pub fn boost(&mut self, multiplier: f32, duration: f32) {
+        self.boost_multiplier = multiplier;
+        self.boost_remaining = duration;
+    }
+
+    /// Holds the current frame until `reset`
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    pub fn reset(&mut self) {
+        self.position = 0;
+        self.timer = 0.0;
+        self.boost_remaining = 0.0;
+        self.frozen = false;
+    }
+
+    pub fn current_frame(&self) -> usize {
+        self.sequence[self.position]
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use macroquad::audio::{load_sound_from_bytes, Sound};
+use macroquad::experimental::coroutines::{start_coroutine, Coroutine};
+use macroquad::prelude::*;
+use crate::collision::AlphaMask;
+use crate::loading_screen;
+use crate::logging;
+use crate::sprite_sheet::{Sprite, SpriteSheet, SHEET_FRAMES, SHEET_IMAGE};
+
+/// Folder every asset path is relative to
+pub const ROOT: &str = "assets";
+
+/// Sound effects loaded before the game starts, music streams in separately once it runs
+pub const SOUNDS: [&str; 4] = [
+    "audio/death-sound-trimmed.ogg",
+    "audio/hit-trimmed.ogg",
+    "audio/point-trimmed.ogg",
+    "audio/wing-trimmed.ogg",
+];
+
+/// The sprite sheet packed by `build.rs` into `OUT_DIR`, always built in
+static SHEET: [(&str, &[u8]); 2] = [
+    (SHEET_IMAGE, include_bytes!(concat!(env!("OUT_DIR"), "/sprites.png"))),
+    (SHEET_FRAMES, include_bytes!(concat!(env!("OUT_DIR"), "/sprites.json"))),
+];
+
+#[cfg(feature = "embed-assets")]
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"));
+}
+
+/// A file built into the executable, by its path under `assets/`:
+/// the sprite sheet, and every other file with the `embed-assets` feature
+pub fn embedded(path: &str) -> Option<&'static [u8]> {
+    let sheet = path
+        .strip_prefix(ROOT)
+        .and_then(|file| file.strip_prefix('/'))
+        .and_then(|file| SHEET.iter().find(|(sheet_file, _)| *sheet_file == file));
+    sheet
+        .or_else(|| embedded_files().iter().find(|(file, _)| *file == path))
+        .map(|&(_, bytes)| bytes)
+}
+
+#[cfg(feature = "embed-assets")]
+fn embedded_files() -> &'static [(&'static str, &'static [u8])] {
+    embedded::FILES
+}
+
+#[cfg(not(feature = "embed-assets"))]
+fn embedded_files() -> &'static [(&'static str, &'static [u8])] {
+    &[]
+}
+
+/// Reads an asset from the executable when embedded, else from disk or the web server
+pub async fn load_bytes(path: &str) -> Result<Vec<u8>, String> {
+    if let Some(bytes) = embedded(path) {
+        return Ok(bytes.to_vec());
+    }
+    load_file(path).await.map_err(|e| format!("Failed to load {}: {}", path, e))
+}
+
+/// A file download running alongside the loading screen
+type Fetch = Coroutine<Result<Vec<u8>, String>>;
+
+/// File of a sprite that isn't on a sheet, under the asset root
+pub fn sprite_file(name: &str) -> String {
+    format!("sprites/{}.png", name)
+}
+
+/// The sprite sheet and `SOUNDS`, relative to the asset root
+pub fn preload_files() -> Vec<String> {
+    [SHEET_IMAGE, SHEET_FRAMES]
+        .into_iter()
+        .chain(SOUNDS)
+        .map(str::to_string)
+        .collect()
+}
+
+/// A loaded texture, sound or text file in an `AssetManager`, cheap to copy and store
+pub struct Handle<T> {
+    index: usize,
+    asset: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> std::fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle({})", self.index)
+    }
+}
+
+/// Loaded assets of one kind, by file
+pub struct Cache<T> {
+    indices: HashMap<String, usize>,
+    items: Vec<T>,
+}
+
+impl<T> Default for Cache<T> {
+    fn default() -> Self {
+        Cache {
+            indices: HashMap::new(),
+            items: Vec::new(),
+        }
+    }
+}
+
+impl<T> Cache<T> {
+    fn insert(&mut self, file: String, item: T) {
+        self.indices.insert(file, self.items.len());
+        self.items.push(item);
+    }
+}
+
+/// Something an `AssetManager` can hand out handles to
+pub trait Asset: Sized {
+    /// What the asset is called in error messages
+    const KIND: &'static str;
+
+    fn cache(assets: &AssetManager) -> &Cache<Self>;
+}
+
+impl Asset for Texture2D {
+    const KIND: &'static str = "texture";
+
+    fn cache(assets: &AssetManager) -> &Cache<Self> {
+        &assets.textures
+    }
+}
+
+impl Asset for Sound {
+    const KIND: &'static str = "sound";
+
+    fn cache(assets: &AssetManager) -> &Cache<Self> {
+        &assets.sounds
+    }
+}
+
+impl Asset for String {
+    const KIND: &'static str = "file";
+
+    fn cache(assets: &AssetManager) -> &Cache<Self> {
+        &assets.texts
+    }
+}
+
+/// Loads and owns every texture and sound under one root folder.
+/// Files are loaded once, later requests for the same file get the cached copy.
+pub struct AssetManager {
+    root: String,
+    textures: Cache<Texture2D>,
+    sounds: Cache<Sound>,
+    texts: Cache<String>,
+    /// Searched before single sprite files
+    sheets: Vec<SpriteSheet>,
+}
+
+impl AssetManager {
+    pub fn new(root: &str) -> Self {
+        AssetManager {
+            root: root.to_string(),
+            textures: Cache::default(),
+            sounds: Cache::default(),
+            texts: Cache::default(),
+            sheets: Vec::new(),
+        }
+    }
+
+    /// Full path of a file under the root
+    pub fn path(&self, file: &str) -> String {
+        format!("{}/{}", self.root, file)
+    }
+
+    fn is_loaded(&self, file: &str) -> bool {
+        self.textures.indices.contains_key(file)
+            || self.sounds.indices.contains_key(file)
+            || self.texts.indices.contains_key(file)
+    }
+
+    /// Loads the files not loaded yet, all at once, drawing the loading screen until they're in.
+    /// `.png` files become textures, `.json` files text and anything else a sound.
+    /// The error names the first file that failed.
+    pub async fn load(&mut self, files: &[String]) -> Result<(), String> {
+        let mut pending: Vec<(String, Fetch)> = Vec::new();
+        for file in files {
+            if !self.is_loaded(file) && !pending.iter().any(|(queued, _)| queued == file) {
+                pending.push((file.clone(), start_coroutine(fetch(self.path(file)))));
+            }
+        }
+        let total = pending.len();
+        let start = get_time();
+
+        while !pending.is_empty() {
+            let mut finished = Vec::new();
+            pending.retain(|(file, coroutine)| match coroutine.retrieve() {
+                Some(result) => {
+                    finished.push((file.clone(), result));
+                    false
+                }
+                None => true,
+            });
+
+            for (file, bytes) in finished {
+                let bytes = bytes?;
+                let path = self.path(&file);
+                let decode_error = |e: &dyn std::fmt::Display| format!("Failed to decode {}: {}", path, e);
+                if file.ends_with(".png") {
+                    let image = Image::from_file_with_format(&bytes, None).map_err(|e| decode_error(&e))?;
+                    let texture = Texture2D::from_image(&image);
+                    // Everything is pixel art
+                    texture.set_filter(FilterMode::Nearest);
+                    self.textures.insert(file, texture);
+                } else if file.ends_with(".json") {
+                    let text = String::from_utf8(bytes).map_err(|e| decode_error(&e))?;
+                    self.texts.insert(file, text);
+                } else {
+                    let sound = load_sound_from_bytes(&bytes).await.map_err(|e| decode_error(&e))?;
+                    self.sounds.insert(file, sound);
+                }
+            }
+
+            clear_background(BLACK);
+            loading_screen::draw_loading_screen(total - pending.len(), total);
+            next_frame().await;
+        }
+
+        logging::info!(Game, "Loaded {} assets in {:.2}s", total, get_time() - start);
+        Ok(())
+    }
+
+    /// Handle to a loaded file, an error if it was never loaded or failed to
+    pub fn handle<T: Asset>(&self, file: &str) -> Result<Handle<T>, String> {
+        T::cache(self)
+            .indices
+            .get(file)
+            .map(|&index| Handle { index, asset: PhantomData })
+            .ok_or_else(|| format!("{} {} is not loaded", T::KIND, self.path(file)))
+    }
+
+    pub fn get<T: Asset>(&self, handle: Handle<T>) -> &T {
+        &T::cache(self).items[handle.index]
+    }
+
+    pub fn texture(&self, file: &str) -> Result<Handle<Texture2D>, String> {
+        self.handle(file)
+    }
+
+    pub fn sound(&self, file: &str) -> Result<Handle<Sound>, String> {
+        self.handle(file)
+    }
+
+    /// Makes the sprites of a loaded sheet available to `sprite`
+    pub fn add_sprite_sheet(&mut self, image: &str, frames: &str) -> Result<(), String> {
+        let texture = self.get(self.texture(image)?).clone();
+        let json = self.get(self.handle::<String>(frames)?);
+        let sheet = SpriteSheet::parse(texture, json).map_err(|e| format!("{}: {}", self.path(frames), e))?;
+        self.sheets.push(sheet);
+        Ok(())
+    }
+
+    /// Solid pixels of a sprite on the packed sheet. It is read from the copy built into the executable
+    /// and decoded on the CPU, so this needs neither `load` nor a window, e.g. for `--verify-replay`.
+    pub fn sprite_mask(&self, name: &str) -> Result<AlphaMask, String> {
+        let file = |file: &str| {
+            let path = self.path(file);
+            embedded(&path).ok_or_else(|| format!("{} is not built in", path))
+        };
+        let frames = std::str::from_utf8(file(SHEET_FRAMES)?)
+            .map_err(|e| format!("{}: {}", self.path(SHEET_FRAMES), e))?;
+        let source = SpriteSheet::find_frame(frames, name)?;
+        AlphaMask::from_png(file(SHEET_IMAGE)?, source).map_err(|e| format!("{}: {}", name, e))
+    }
+
+    /// A sprite by name, from the last added sheet that has it or else its own loaded file
+    pub fn sprite(&self, name: &str) -> Result<Sprite, String> {
+        if let Some(sprite) = self.sheets.iter().rev().find_map(|sheet| sheet.sprite(name)) {
+            return Ok(sprite);
+        }
+        self.texture(&sprite_file(name))
+            .map(|handle| Sprite::whole(self.get(handle).clone()))
+            .map_err(|_| format!("No sprite named {}", name))
+    }
+}
+
+async fn fetch(path: String) -> Result<Vec<u8>, String> {
+    load_bytes(&path).await
+}
@@ -0,0 +1,47 @@
+/// Music volume while the game is paused, relative to the music bus
+const DUCK_VOLUME: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bus {
+    Music,
+    Sfx,
+}
+
+/// Volume buses every sound plays through: master scales both music and effects.
+/// Players ask for their bus volume when they start or update a sound, so changes apply right away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioMixer {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub muted: bool,
+    /// Lowers the music while something else has the player's attention, e.g. the pause screen
+    pub ducked: bool,
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        AudioMixer {
+            master: 1.0,
+            music: 1.0,
+            sfx: 1.0,
+            muted: false,
+            ducked: false,
+        }
+    }
+}
+
+impl AudioMixer {
+    /// Final volume for a sound on `bus`, before any fade of its own
+    pub fn volume(&self, bus: Bus) -> f32 {
+        if self.muted {
+            return 0.0;
+        }
+
+        match bus {
+            Bus::Music if self.ducked => self.master * self.music * DUCK_VOLUME,
+            Bus::Music => self.master * self.music,
+            Bus::Sfx => self.master * self.sfx,
+        }
+    }
+}
@@ -1,58 +1,39 @@
-use macroquad::texture::{load_texture, FilterMode, Texture2D};
-
-pub enum BackgroundType {
-    Day,
-    Night,
+use crate::SCREEN_WIDTH;
+use crate::sprite_sheet::Sprite;
+use crate::theme::SpriteCycle;
+
+/// A strip of the background scrolling at `speed` times the world's velocity
+pub struct BackgroundLayer {
+    pub sprites: SpriteCycle,
+    /// Top and bottom rows of the sprites this layer shows, all of them when `None`
+    pub rows: Option<(f32, f32)>,
+    /// 0 stays put, 1 keeps up with the pipes
+    pub speed: f32,
 }
 
-impl BackgroundType {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            BackgroundType::Day => "day",
-            BackgroundType::Night => "night",
-        }
+impl BackgroundLayer {
+    pub fn get_texture_2d(&self, score: u32) -> &Sprite {
+        self.sprites.for_score(score)
     }
-}
 
-pub struct BackgroundSprites {
-    pub day_texture: Texture2D,
-    pub night_texture: Texture2D,
+    /// Drawn stretched over the screen, so it repeats every screen width
+    pub fn tile_width(&self) -> f32 {
+        SCREEN_WIDTH
+    }
 }
 
+/// Background layers, drawn back to front
 pub struct BackgroundTextureAtlas {
-    pub background_sprites: BackgroundSprites,
+    pub layers: Vec<BackgroundLayer>,
 }
 
 impl BackgroundTextureAtlas {
-    pub async fn new() -> BackgroundTextureAtlas {
-        let day = Self::get_texture("day").await;
-        let night = Self::get_texture("night").await;
-
-        let textures = [&day, &night];
-        for t in textures {
-            t.set_filter(FilterMode::Nearest);
-        }
-
-        BackgroundTextureAtlas {
-            background_sprites: BackgroundSprites {
-                day_texture: day,
-                night_texture: night,
-            },
-        }
-    }
-
-    async fn get_texture(background_type: &str) -> Texture2D {
-        let path = format!("assets/sprites/background-{}.png", background_type);
-        load_texture(&path)
-            .await
-            .expect("Failed to load texture")
+    pub fn new(layers: Vec<BackgroundLayer>) -> BackgroundTextureAtlas {
+        BackgroundTextureAtlas { layers }
     }
 
-    pub fn get_texture_2d(&self, background_type: BackgroundType) -> &Texture2D {
-        if background_type.as_str() == BackgroundType::Day.as_str() {
-            &self.background_sprites.day_texture
-        } else {
-            &self.background_sprites.night_texture
-        }
+    /// Speed and tile width of each layer, for `Parallax::set_layers`
+    pub fn scrolls(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.layers.iter().map(|layer| (layer.speed, layer.tile_width()))
     }
-}
\ No newline at end of file
+}
@@ -1,117 +1,96 @@
-use macroquad::color::WHITE;
-use macroquad::math::vec2;
-use macroquad::prelude::{draw_texture_ex, DrawTextureParams};
-use crate::base_texture_atlas::BaseTextureAtlas;
-use crate::components::Node;
-use crate::player::Player;
+use macroquad::math::Rect;
+use crate::collision::Shape;
+use crate::components::{Node, Size};
 use crate::SCREEN_HEIGHT;
 
 pub struct Base {
-    pub base_texture_atlas: BaseTextureAtlas,
+    pub size: Size,
     pub velocity: u16,
     pub height: f32,
     pub x1: f32,
     pub x2: f32,
+    previous_x1: f32,
+    previous_x2: f32,
     pub stopped: bool,
 }
 
 
 impl Base {
-    pub async fn new(base_texture_atlas: BaseTextureAtlas,
-                     velocity: u16) -> Base {
-        let height = base_texture_atlas.height;
-        let width = base_texture_atlas.width;
+    pub fn new(size: Size,
+               velocity: u16) -> Base {
         Base {
-            base_texture_atlas,
+            size,
             velocity,
-            height,
+            height: size.height,
             x1: 0.0,
-            x2: width,
+            x2: size.width,
+            previous_x1: 0.0,
+            previous_x2: size.width,
             stopped: false,
         }
     }
 
     pub fn stop(&mut self) {
         self.stopped = true;
+        self.snap_previous();
     }
 
     pub fn restart(&mut self) {
         self.stopped = false;
+        self.snap_previous();
     }
 
-    pub fn touched(&self, player: &Player) -> bool {
-        let width = self.base_texture_atlas.width;
-        let height = self.base_texture_atlas.height;
-        let y = SCREEN_HEIGHT - height;
+    fn snap_previous(&mut self) {
+        self.previous_x1 = self.x1;
+        self.previous_x2 = self.x2;
+    }
 
-        let player_x = player.position.x;
-        let player_y = player.position.y;
-        let player_width = player.bird_renderer.width;
-        let player_height = player.bird_renderer.height;
+    /// X of both tiles to draw at, between the last two fixed updates
+    pub fn interpolated_x(&self, alpha: f32) -> (f32, f32) {
+        (
+            self.previous_x1 + (self.x1 - self.previous_x1) * alpha,
+            self.previous_x2 + (self.x2 - self.previous_x2) * alpha,
+        )
+    }
 
-        // Check collision with first base
-        let collision1 = self.x1 < player_x + player_width
-            && self.x1 + width > player_x
-            && y < player_y + player_height
-            && y + height > player_y;
+    /// Hitboxes of both tiles
+    pub fn hit_rects(&self) -> [Rect; 2] {
+        let width = self.size.width;
+        let height = self.size.height;
+        let y = SCREEN_HEIGHT - height;
 
-        // Check collision with second base
-        let collision2 = self.x2 < player_x + player_width
-            && self.x2 + width > player_x
-            && y < player_y + player_height
-            && y + height > player_y;
+        [Rect::new(self.x1, y, width, height), Rect::new(self.x2, y, width, height)]
+    }
 
-        collision1 || collision2
+    pub fn touched(&self, bird: &Shape) -> bool {
+        // Check collision with both tiles
+        self.hit_rects().iter().any(|rect| bird.overlaps_rect(rect))
     }
 }
 
 impl Node for Base {
     fn update(&mut self, dt: f32) {
+        self.snap_previous();
+
         if !self.stopped {
             let speed = self.velocity as f32 * dt;
             self.x1 -= speed;
             self.x2 -= speed;
 
-            let width = self.base_texture_atlas.width;
+            let width = self.size.width;
 
+            // Wrapped tiles move their previous position along so they don't slide across the screen
             if self.x1 + width <= 0.0 {
-                self.x1 = self.x2 + width;
+                let wrapped = self.x2 + width;
+                self.previous_x1 += wrapped - self.x1;
+                self.x1 = wrapped;
             }
 
             if self.x2 + width <= 0.0 {
-                self.x2 = self.x1 + width;
+                let wrapped = self.x1 + width;
+                self.previous_x2 += wrapped - self.x2;
+                self.x2 = wrapped;
             }
         }
     }
-
-    fn draw(&mut self) {
-        let width = self.base_texture_atlas.width;
-        let height = self.base_texture_atlas.height;
-        let y = SCREEN_HEIGHT - height;
-        let texture = &self.base_texture_atlas.texture;
-
-        // Draw first base
-        draw_texture_ex(
-            texture,
-            self.x1,
-            y,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(vec2(width, height)),
-                ..Default::default()
-            },
-        );
-
-        // Draw second base
-        draw_texture_ex(
-            texture,
-            self.x2,
-            y,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(vec2(width, height)),
-                ..Default::default()
-            },
-        );
-    }
 }
@@ -0,0 +1,46 @@
+use macroquad::math::vec2;
+use macroquad::prelude::DrawTextureParams;
+use crate::base::Base;
+use crate::base_texture_atlas::BaseTextureAtlas;
+use crate::components::Renderer;
+use crate::SCREEN_HEIGHT;
+
+pub struct BaseRenderer {
+    pub base_texture_atlas: BaseTextureAtlas,
+}
+
+impl BaseRenderer {
+    pub fn new(base_texture_atlas: BaseTextureAtlas) -> Self {
+        BaseRenderer { base_texture_atlas }
+    }
+}
+
+impl Renderer<Base> for BaseRenderer {
+    fn draw(&mut self, base: &Base, alpha: f32) {
+        let (x1, x2) = base.interpolated_x(alpha);
+        let width = base.size.width;
+        let height = base.size.height;
+        let y = SCREEN_HEIGHT - height;
+        let texture = &self.base_texture_atlas.texture;
+
+        // Draw first base
+        texture.draw(
+            x1,
+            y,
+            DrawTextureParams {
+                dest_size: Some(vec2(width, height)),
+                ..Default::default()
+            },
+        );
+
+        // Draw second base
+        texture.draw(
+            x2,
+            y,
+            DrawTextureParams {
+                dest_size: Some(vec2(width, height)),
+                ..Default::default()
+            },
+        );
+    }
+}
@@ -1,30 +1,11 @@
-use macroquad::texture::{load_texture, FilterMode, Texture2D};
+use crate::sprite_sheet::Sprite;
 
 pub struct BaseTextureAtlas {
-    pub texture: Texture2D,
-    pub width: f32,
-    pub height: f32,
+    pub texture: Sprite,
 }
 
 impl BaseTextureAtlas {
-    pub async fn new() -> BaseTextureAtlas {
-        let texture = Self::get_texture().await;
-        texture.set_filter(FilterMode::Nearest);
-
-        let w = texture.width();
-        let h = texture.height();
-
-        BaseTextureAtlas {
-            texture,
-            width: w,
-            height: h,
-        }
+    pub fn new(texture: Sprite) -> BaseTextureAtlas {
+        BaseTextureAtlas { texture }
     }
-
-    pub async fn get_texture() -> Texture2D {
-        let path = "assets/sprites/base.png";
-        load_texture(path)
-            .await
-            .expect("Failed to load texture")
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,279 @@
+use macroquad::math::{vec2, Rect, Vec2};
+use macroquad::prelude::ImageFormat;
+use macroquad::texture::Image;
+use crate::platform;
+
+/// Hitbox of the bird, relative to its sprite rectangle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BirdShape {
+    /// The sprite rectangle shrunk by `inset` pixels on every side, ignores rotation
+    InsetRect { inset: f32 },
+    /// A circle around the sprite center
+    Circle { radius: f32 },
+    /// A horizontal segment through the sprite center grown by `radius`, rotated with the bird
+    Capsule { half_length: f32, radius: f32 },
+}
+
+impl Default for BirdShape {
+    /// Covers the body of the 34x24 sprite without the transparent corners
+    fn default() -> Self {
+        BirdShape::Capsule { half_length: 6.0, radius: 10.0 }
+    }
+}
+
+impl BirdShape {
+    /// `rect`, `circle` or `capsule`, sized for the bundled sprites
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rect" => Some(BirdShape::InsetRect { inset: 3.0 }),
+            "circle" => Some(BirdShape::Circle { radius: 11.0 }),
+            "capsule" => Some(BirdShape::default()),
+            _ => None,
+        }
+    }
+
+    /// Picked with `--bird-hitbox <name>` (or `?bird-hitbox=` on the web)
+    pub fn from_launch_options() -> Self {
+        platform::arg("bird-hitbox")
+            .and_then(|name| Self::from_name(&name))
+            .unwrap_or_default()
+    }
+
+    /// Places the shape over a sprite drawn at `position` with `size`, rotated by `rotation` radians
+    pub fn at(&self, position: Vec2, size: Vec2, rotation: f32) -> Shape {
+        let center = position + size / 2.0;
+        match *self {
+            BirdShape::InsetRect { inset } => Shape::Rect(Rect::new(
+                position.x + inset,
+                position.y + inset,
+                (size.x - inset * 2.0).max(0.0),
+                (size.y - inset * 2.0).max(0.0),
+            )),
+            BirdShape::Circle { radius } => Shape::Circle { center, radius },
+            BirdShape::Capsule { half_length, radius } => {
+                let axis = Vec2::from_angle(rotation) * half_length;
+                Shape::Capsule { a: center - axis, b: center + axis, radius }
+            }
+        }
+    }
+}
+
+/// Hitbox of a pipe
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum PipeShape {
+    /// The whole sprite rectangle
+    #[default]
+    Rect,
+    /// Only the opaque pixels of the sprite
+    Mask(AlphaMask),
+}
+
+/// Which pixels of a sprite are solid, row-major
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlphaMask {
+    width: usize,
+    height: usize,
+    solid: Vec<bool>,
+}
+
+impl AlphaMask {
+    /// Pixels with alpha above `threshold` count as solid
+    pub fn from_rgba(width: usize, height: usize, rgba: &[u8], threshold: u8) -> Self {
+        let solid = rgba.chunks_exact(4).map(|pixel| pixel[3] > threshold).collect();
+        AlphaMask { width, height, solid }
+    }
+
+    /// Decodes `source` of a PNG on the CPU, so it also works without a window
+    pub fn from_png(bytes: &[u8], source: Rect) -> Result<Self, String> {
+        let image = Image::from_file_with_format(bytes, Some(ImageFormat::Png))
+            .map_err(|e| format!("Failed to decode mask: {}", e))?
+            .sub_image(source);
+        Ok(Self::from_rgba(image.width as usize, image.height as usize, &image.bytes, 127))
+    }
+
+    fn is_solid(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.solid[y * self.width + x]
+    }
+}
+
+/// A hitbox placed in world space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Rect(Rect),
+    Circle { center: Vec2, radius: f32 },
+    Capsule { a: Vec2, b: Vec2, radius: f32 },
+}
+
+impl Shape {
+    pub fn bounds(&self) -> Rect {
+        match *self {
+            Shape::Rect(rect) => rect,
+            Shape::Circle { center, radius } => {
+                Rect::new(center.x - radius, center.y - radius, radius * 2.0, radius * 2.0)
+            }
+            Shape::Capsule { a, b, radius } => {
+                let min = a.min(b) - Vec2::splat(radius);
+                let max = a.max(b) + Vec2::splat(radius);
+                Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+            }
+        }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        match *self {
+            Shape::Rect(rect) => rect.contains(point),
+            Shape::Circle { center, radius } => center.distance_squared(point) <= radius * radius,
+            Shape::Capsule { a, b, radius } => segment_point_distance(a, b, point) <= radius,
+        }
+    }
+
+    pub fn overlaps_rect(&self, rect: &Rect) -> bool {
+        match *self {
+            Shape::Rect(own) => own.overlaps(rect),
+            Shape::Circle { center, radius } => rect_point_distance(rect, center) <= radius,
+            Shape::Capsule { a, b, radius } => segment_rect_distance(a, b, rect) <= radius,
+        }
+    }
+
+    /// Tests against the solid pixels of `mask` stretched over `rect`, flipped when `flip_y`
+    pub fn overlaps_mask(&self, mask: &AlphaMask, rect: &Rect, flip_y: bool) -> bool {
+        let Some(overlap) = self.bounds().intersect(*rect) else {
+            return false;
+        };
+
+        let scale = vec2(mask.width as f32 / rect.w, mask.height as f32 / rect.h);
+        let first_x = ((overlap.x - rect.x) * scale.x).floor() as usize;
+        let last_x = ((overlap.right() - rect.x) * scale.x).ceil() as usize;
+        let first_y = ((overlap.y - rect.y) * scale.y).floor() as usize;
+        let last_y = ((overlap.bottom() - rect.y) * scale.y).ceil() as usize;
+
+        for y in first_y..last_y.min(mask.height) {
+            let mask_y = if flip_y { mask.height - 1 - y } else { y };
+            for x in first_x..last_x.min(mask.width) {
+                if !mask.is_solid(x, mask_y) {
+                    continue;
+                }
+                // Test the center of the pixel in world space
+                let point = vec2(
+                    rect.x + (x as f32 + 0.5) / scale.x,
+                    rect.y + (y as f32 + 0.5) / scale.y,
+                );
+                if self.contains(point) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+fn rect_point_distance(rect: &Rect, point: Vec2) -> f32 {
+    let closest = point.clamp(rect.point(), rect.point() + rect.size());
+    closest.distance(point)
+}
+
+fn segment_point_distance(a: Vec2, b: Vec2, point: Vec2) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared == 0.0 {
+        return a.distance(point);
+    }
+    let t = ((point - a).dot(ab) / length_squared).clamp(0.0, 1.0);
+    (a + ab * t).distance(point)
+}
+
+fn segments_intersect(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let cross = |o: Vec2, p: Vec2, q: Vec2| (p - o).perp_dot(q - o);
+    let d1 = cross(c, d, a);
+    let d2 = cross(c, d, b);
+    let d3 = cross(a, b, c);
+    let d4 = cross(a, b, d);
+    (d1 > 0.0) != (d2 > 0.0) && (d3 > 0.0) != (d4 > 0.0)
+}
+
+fn segment_rect_distance(a: Vec2, b: Vec2, rect: &Rect) -> f32 {
+    let corners = [
+        rect.point(),
+        vec2(rect.right(), rect.y),
+        vec2(rect.right(), rect.bottom()),
+        vec2(rect.x, rect.bottom()),
+    ];
+
+    if rect.contains(a) || rect.contains(b) {
+        return 0.0;
+    }
+    for i in 0..4 {
+        if segments_intersect(a, b, corners[i], corners[(i + 1) % 4]) {
+            return 0.0;
+        }
+    }
+
+    // Apart: the closest points involve an endpoint of the segment or a corner of the rect
+    let endpoints = rect_point_distance(rect, a).min(rect_point_distance(rect, b));
+    corners
+        .iter()
+        .map(|&corner| segment_point_distance(a, b, corner))
+        .fold(endpoints, f32::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capsule_against_rect() {
+        let capsule = Shape::Capsule { a: vec2(0.0, 0.0), b: vec2(10.0, 0.0), radius: 2.0 };
+        assert!(capsule.overlaps_rect(&Rect::new(11.5, -1.0, 5.0, 2.0)));
+        assert!(!capsule.overlaps_rect(&Rect::new(13.0, -1.0, 5.0, 2.0)));
+        // Crossing a thin rect with both ends outside it
+        assert!(capsule.overlaps_rect(&Rect::new(4.0, -5.0, 2.0, 10.0)));
+
+        // Diagonal, passing a corner
+        let diagonal = Shape::Capsule { a: vec2(0.0, 0.0), b: vec2(10.0, 10.0), radius: 1.0 };
+        assert!(!diagonal.overlaps_rect(&Rect::new(6.0, 0.0, 4.0, 3.0)));
+        assert!(diagonal.overlaps_rect(&Rect::new(6.0, 0.0, 4.0, 5.5)));
+    }
+
+    #[test]
+    fn circle_against_rect() {
+        let circle = Shape::Circle { center: vec2(0.0, 0.0), radius: 5.0 };
+        assert!(circle.overlaps_rect(&Rect::new(3.0, 3.0, 2.0, 2.0)));
+        assert!(!circle.overlaps_rect(&Rect::new(4.0, 4.0, 2.0, 2.0)));
+        assert!(circle.overlaps_rect(&Rect::new(-10.0, -10.0, 20.0, 20.0)));
+    }
+
+    #[test]
+    fn inset_rect_ignores_rotation() {
+        let shape = BirdShape::InsetRect { inset: 3.0 };
+        let hitbox = shape.at(vec2(0.0, 0.0), vec2(34.0, 24.0), 1.0);
+        assert_eq!(hitbox, Shape::Rect(Rect::new(3.0, 3.0, 28.0, 18.0)));
+        assert!(hitbox.overlaps_rect(&Rect::new(30.0, 0.0, 5.0, 5.0)));
+        assert!(!hitbox.overlaps_rect(&Rect::new(31.5, 0.0, 5.0, 5.0)));
+    }
+
+    /// 4x4 mask with only its first row solid
+    fn top_row_mask() -> AlphaMask {
+        let rgba: Vec<u8> = (0..16).flat_map(|i| [0, 0, 0, if i < 4 { 255 } else { 0 }]).collect();
+        AlphaMask::from_rgba(4, 4, &rgba, 127)
+    }
+
+    #[test]
+    fn mask_against_unflipped_pipe() {
+        let mask = top_row_mask();
+        let pipe = Rect::new(0.0, 0.0, 40.0, 40.0);
+        let near_top = Shape::Circle { center: vec2(5.0, 5.0), radius: 1.0 };
+        let near_bottom = Shape::Circle { center: vec2(5.0, 35.0), radius: 1.0 };
+        assert!(near_top.overlaps_mask(&mask, &pipe, false));
+        assert!(!near_bottom.overlaps_mask(&mask, &pipe, false));
+    }
+
+    #[test]
+    fn mask_against_flipped_pipe() {
+        let mask = top_row_mask();
+        let pipe = Rect::new(0.0, 0.0, 40.0, 40.0);
+        let near_top = Shape::Circle { center: vec2(5.0, 5.0), radius: 1.0 };
+        let near_bottom = Shape::Circle { center: vec2(5.0, 35.0), radius: 1.0 };
+        assert!(!near_top.overlaps_mask(&mask, &pipe, true));
+        assert!(near_bottom.overlaps_mask(&mask, &pipe, true));
+    }
+}
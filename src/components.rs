@@ -1,4 +1,25 @@
 pub trait Node {
     fn update(&mut self, dt: f32);
-    fn draw(&mut self);
-}
\ No newline at end of file
+}
+
+/// Draws a piece of simulation state. Renderers own the textures,
+/// the state they draw stays plain data.
+///
+/// `alpha` is how far we are between the previous and the current fixed update (0..1),
+/// nodes keep their previous position so renderers can interpolate between the two.
+pub trait Renderer<T> {
+    fn draw(&mut self, node: &T, alpha: f32);
+}
+
+/// Width and height of a hitbox, taken from the sprite sizes when rendering
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Size {
+    pub const fn new(width: f32, height: f32) -> Self {
+        Size { width, height }
+    }
+}
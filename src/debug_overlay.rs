@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+use macroquad::prelude::*;
+use crate::collision::{PipeShape, Shape};
+use crate::input::Action;
+use crate::simulation::{Simulation, FIXED_DELTA};
+use crate::SCREEN_WIDTH;
+
+/// Frames kept for the frame-time graph, two seconds at 60 FPS
+const HISTORY: usize = 120;
+const RECENT_INPUTS: usize = 6;
+const GRAPH_HEIGHT: f32 = 48.0;
+/// Frame time at the top of the graph
+const GRAPH_MAX: f32 = FIXED_DELTA * 3.0;
+const FONT_SIZE: f32 = 14.0;
+const LINE_HEIGHT: f32 = 14.0;
+
+/// What the main loop did during one frame
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameStats {
+    /// Smoothed frame time fed to the accumulator
+    pub delta: f32,
+    pub fixed_updates: u32,
+    /// Time left over for the next frame, drives the interpolation alpha
+    pub accumulator: f32,
+    /// The accumulator hit `MAX_FRAME_TIME`
+    pub frame_time_capped: bool,
+    /// `MAX_FIXED_UPDATES_PER_FRAME` was hit and time was discarded
+    pub updates_capped: bool,
+}
+
+/// On-screen timing and input diagnostics, toggled with the `debug` action.
+/// Meant for devices without a console, like mobile browsers.
+pub struct DebugOverlay {
+    pub visible: bool,
+    frames: VecDeque<FrameStats>,
+    inputs: VecDeque<String>,
+    frame_time_caps: u32,
+    update_caps: u32,
+}
+
+impl DebugOverlay {
+    pub fn new(visible: bool) -> Self {
+        DebugOverlay {
+            visible,
+            frames: VecDeque::with_capacity(HISTORY),
+            inputs: VecDeque::with_capacity(RECENT_INPUTS),
+            frame_time_caps: 0,
+            update_caps: 0,
+        }
+    }
+
+    pub fn record_frame(&mut self, stats: FrameStats) {
+        if self.frames.len() == HISTORY {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(stats);
+        self.frame_time_caps += stats.frame_time_capped as u32;
+        self.update_caps += stats.updates_capped as u32;
+    }
+
+    pub fn record_inputs(&mut self, frame: u64, actions: &[Action]) {
+        if self.inputs.len() == RECENT_INPUTS {
+            self.inputs.pop_front();
+        }
+        self.inputs.push_back(format!("frame {}: {:?}", frame, actions));
+    }
+
+    /// `input_frames` and `fixed_updates` are the totals counted by `GameState`
+    pub fn draw(&self, simulation: &Simulation, input_frames: u64, fixed_updates: u64) {
+        if !self.visible {
+            return;
+        }
+
+        self.draw_hitboxes(simulation);
+
+        let lines = 7 + self.inputs.len();
+        let panel_height = GRAPH_HEIGHT + 12.0 + lines as f32 * LINE_HEIGHT;
+        draw_rectangle(0.0, 0.0, SCREEN_WIDTH, panel_height, Color::new(0.0, 0.0, 0.0, 0.6));
+
+        self.draw_graph(4.0, 4.0, SCREEN_WIDTH - 8.0);
+
+        let last = self.frames.back().copied().unwrap_or_default();
+        let total_time: f32 = self.frames.iter().map(|frame| frame.delta).sum();
+        let fps = if total_time > 0.0 { self.frames.len() as f32 / total_time } else { 0.0 };
+        let worst = self.frames.iter().map(|frame| frame.delta).fold(0.0, f32::max);
+
+        let mut y = GRAPH_HEIGHT + 8.0 + LINE_HEIGHT;
+        let mut line = |text: &str, color: Color| {
+            draw_text(text, 4.0, y, FONT_SIZE, color);
+            y += LINE_HEIGHT;
+        };
+
+        line(&format!("FPS {:.1}  frame {:.1} ms  worst {:.1} ms", fps, last.delta * 1000.0, worst * 1000.0), WHITE);
+        line(&format!("fixed steps this frame {}", last.fixed_updates), steps_color(&last));
+        line(
+            &format!("accumulator {:.2} ms  alpha {:.2}", last.accumulator * 1000.0, last.accumulator / FIXED_DELTA),
+            WHITE,
+        );
+        let caps_color = if self.frame_time_caps + self.update_caps > 0 { ORANGE } else { WHITE };
+        line(&format!("capped frames {}  capped updates {}", self.frame_time_caps, self.update_caps), caps_color);
+        line(&format!("input frames {}  fixed updates {}", input_frames, fixed_updates), WHITE);
+        line(&format!("scene {:?}  tick {}", simulation.scene, simulation.tick), WHITE);
+        line("last inputs:", GRAY);
+        for input in self.inputs.iter().rev() {
+            line(input, WHITE);
+        }
+    }
+
+    /// One bar per frame, colored by how many fixed steps it ran
+    fn draw_graph(&self, x: f32, y: f32, width: f32) {
+        draw_rectangle_lines(x, y, width, GRAPH_HEIGHT, 1.0, GRAY);
+
+        let bar_width = width / HISTORY as f32;
+        for (i, frame) in self.frames.iter().enumerate() {
+            let height = (frame.delta / GRAPH_MAX).min(1.0) * GRAPH_HEIGHT;
+            let bar_x = x + i as f32 * bar_width;
+            draw_rectangle(bar_x, y + GRAPH_HEIGHT - height, bar_width, height, steps_color(frame));
+        }
+
+        // Target frame time
+        let target_y = y + GRAPH_HEIGHT - FIXED_DELTA / GRAPH_MAX * GRAPH_HEIGHT;
+        draw_line(x, target_y, x + width, target_y, 1.0, SKYBLUE);
+    }
+
+    /// Hitboxes at the last fixed update, sprites are drawn slightly behind them
+    fn draw_hitboxes(&self, simulation: &Simulation) {
+        let world = &simulation.world;
+        let pipe_color = match world.pipe_shape {
+            PipeShape::Rect => RED,
+            // Only the opaque pixels collide, the outline is the sprite bounds
+            PipeShape::Mask(_) => ORANGE,
+        };
+
+        for (top, bottom) in &world.pipes {
+            draw_shape(&Shape::Rect(top.hit_rect()), pipe_color);
+            draw_shape(&Shape::Rect(bottom.hit_rect()), pipe_color);
+        }
+        for rect in world.base.hit_rects() {
+            draw_shape(&Shape::Rect(rect), RED);
+        }
+        draw_shape(&simulation.player.hitbox(), LIME);
+    }
+}
+
+fn steps_color(frame: &FrameStats) -> Color {
+    match frame.fixed_updates {
+        _ if frame.frame_time_capped || frame.updates_capped => RED,
+        1 => GREEN,
+        0 | 2 => YELLOW,
+        _ => ORANGE,
+    }
+}
+
+fn draw_shape(shape: &Shape, color: Color) {
+    match *shape {
+        Shape::Rect(rect) => draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, color),
+        Shape::Circle { center, radius } => draw_circle_lines(center.x, center.y, radius, 1.0, color),
+        Shape::Capsule { a, b, radius } => {
+            let offset = (b - a).normalize_or_zero().perp() * radius;
+            draw_circle_lines(a.x, a.y, radius, 1.0, color);
+            draw_circle_lines(b.x, b.y, radius, 1.0, color);
+            draw_line(a.x + offset.x, a.y + offset.y, b.x + offset.x, b.y + offset.y, 1.0, color);
+            draw_line(a.x - offset.x, a.y - offset.y, b.x - offset.x, b.y - offset.y, 1.0, color);
+        }
+    }
+}
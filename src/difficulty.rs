@@ -0,0 +1,57 @@
+/// How hard the game is from `min_score` onwards
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyLevel {
+    pub min_score: u32,
+    /// Scroll speed of pipes and base, in pixels per second
+    pub speed: u16,
+    /// Seconds between two pipe pairs
+    pub spawn_interval: f32,
+    /// Vertical opening between the top and bottom pipe
+    pub gap_size: f32,
+    /// How many `PipeLocation` steps the next gap may move from the previous one
+    pub max_location_jump: usize,
+}
+
+const fn level(min_score: u32, speed: u16, spawn_interval: f32, gap_size: f32, max_location_jump: usize) -> DifficultyLevel {
+    DifficultyLevel { min_score, speed, spawn_interval, gap_size, max_location_jump }
+}
+
+/// Score 0 matches the original fixed values, then the game tightens up every few points
+pub const DEFAULT_LEVELS: [DifficultyLevel; 6] = [
+    level(0, 130, 2.0, 140.0, 9),
+    level(5, 140, 1.9, 135.0, 6),
+    level(10, 150, 1.8, 130.0, 5),
+    level(20, 165, 1.7, 125.0, 4),
+    level(30, 180, 1.6, 120.0, 4),
+    level(50, 200, 1.5, 115.0, 3),
+];
+
+/// Difficulty as a function of score, a list of levels sorted by `min_score`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DifficultyCurve {
+    levels: Vec<DifficultyLevel>,
+}
+
+impl DifficultyCurve {
+    pub fn new(mut levels: Vec<DifficultyLevel>) -> Self {
+        assert!(!levels.is_empty(), "A difficulty curve needs at least one level");
+        levels.sort_by_key(|level| level.min_score);
+        DifficultyCurve { levels }
+    }
+
+    /// The last level whose `min_score` has been reached
+    pub fn at(&self, score: u32) -> DifficultyLevel {
+        self.levels
+            .iter()
+            .rev()
+            .find(|level| level.min_score <= score)
+            .copied()
+            .unwrap_or(self.levels[0])
+    }
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        DifficultyCurve::new(DEFAULT_LEVELS.to_vec())
+    }
+}
@@ -1,66 +1,228 @@
 use macroquad::prelude::*;
 use crate::{SCREEN_WIDTH, SCREEN_HEIGHT};
-use crate::background_texture_atlas::BackgroundTextureAtlas;
-use crate::components::Node;
-use crate::music_player::MusicPlayer;
-use crate::player::Player;
+use crate::assets::AssetManager;
+use crate::audio_mixer::AudioMixer;
+use crate::base_renderer::BaseRenderer;
+use crate::collision::{BirdShape, PipeShape};
+use crate::components::Renderer;
+use crate::debug_overlay::{DebugOverlay, FrameStats};
+use crate::high_score::HighScore;
+use crate::input::{Action, Input, InputMap};
+use crate::logging;
+use crate::music_player::{MusicPlayer, MusicState};
+use crate::pipe_renderer::PipeRenderer;
+use crate::pipe_texture_atlas::MASK_SPRITE;
+use crate::platform::{self, FocusWatcher};
+use crate::player_renderer::PlayerRenderer;
+use crate::replay::{Replay, ReplayPlayback, ReplayRecorder};
+use crate::rng::{seed_from_str, Rng, SeedMode};
+use crate::settings::Settings;
+use crate::settings_menu::{self, Choices, MenuOutcome, SettingsMenu};
+use crate::simulation::{GameScene, Hitboxes, SimEvent, Simulation};
 use crate::sound_effects::SoundEffects;
-use crate::world::World;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum GameScene {
-    StartScreen,
-    Playing,
-    GameOver,
-}
+use crate::sprite_sheet::Sprite;
+use crate::storage::{self, Storage};
+use crate::theme::{self, Theme, ThemeInfo, DEFAULT_THEME};
+use crate::world_renderer::WorldRenderer;
 
 pub struct GameState {
-    scene: GameScene,
+    /// Kept for loading themes picked in the settings
+    assets: AssetManager,
+    themes: Vec<ThemeInfo>,
+    /// Theme the renderers were built from
+    theme_id: String,
+    simulation: Simulation,
     music_player: MusicPlayer,
     sound_effects: SoundEffects,
-    world: World,
-    player: Player,
-    gameover_texture: Texture2D,
-    // Input buffering: Store inputs detected between fixed updates
-    input_buffer_space: bool,
-    input_buffer_mouse: bool,
+    audio_mixer: AudioMixer,
+    world_renderer: WorldRenderer,
+    player_renderer: PlayerRenderer,
+    message_sprite: Sprite,
+    gameover_sprite: Sprite,
+    high_score: HighScore,
+    new_high_score: bool,
+    // Replays: every run is recorded, saved when --record-replay is given
+    replay_recorder: ReplayRecorder,
+    replay_path: Option<String>,
+    replay_playback: Option<ReplayPlayback>,
+    // Input buffering: Store actions detected between fixed updates
+    input: Input,
+    focus_watcher: FocusWatcher,
+    focus_lost: bool,
+    /// Mouse or touch press since the last fixed update, for the menus
+    pointer_press: Option<Vec2>,
+    settings: Settings,
+    settings_storage: Box<dyn Storage>,
+    settings_menu: SettingsMenu,
+    /// Picks the bird while the bird setting is on random, whatever the theme's bird count
+    random_bird: usize,
+    debug_overlay: DebugOverlay,
+    /// Shown by `--debug-overlay` for this session only, without saving it in the settings
+    debug_overlay_forced: bool,
     // Debug: Track input detection
     debug_input_frame_count: u64,
     debug_fixed_update_count: u64,
-    debug_inputs_detected: Vec<String>,
 }
 
 impl GameState {
-    pub async fn new() -> Self {
-
-        let background_texture_atlas = BackgroundTextureAtlas::new().await;
-        let player = Player::new().await;
-        let world = World::new(background_texture_atlas).await;
-        let music_player = MusicPlayer::new("assets/music", 2.0)
-            .await
-            .expect("Failed to load music");
-        let sound_effects = SoundEffects::new().await;
-        let gameover_texture = load_texture("assets/sprites/gameover.png")
-            .await
-            .expect("Failed to load gameover texture");
-        Self {
-            scene: GameScene::StartScreen,
+    /// Builds the game from the preloaded sprites and sounds, loads the theme and starts loading the music
+    pub async fn new(mut assets: AssetManager) -> Result<Self, String> {
+        // --replay <file> (or ?replay= on the web) plays a recorded run instead of live input
+        let replay_playback = match platform::arg("replay") {
+            Some(path) => match Replay::load(&path).await {
+                Ok(replay) => Some(ReplayPlayback::new(replay)),
+                Err(e) => {
+                    logging::error!(Game, "{}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        // A --seed flag (or ?seed= on the web) fixes the pipes and bird for every run
+        let seed_mode = match (&replay_playback, platform::arg("seed")) {
+            (Some(playback), _) => SeedMode::Fixed(playback.seed()),
+            (None, Some(seed)) => SeedMode::Fixed(seed_from_str(&seed)),
+            (None, None) => SeedMode::Session(miniquad::date::now().to_bits()),
+        };
+        logging::info!(Game, "Seed: {:?}", seed_mode);
+        let random_bird = Rng::new(seed_mode.seed()).gen_range(0, 1 << 16);
+
+        let settings_storage = storage::open();
+        let mut settings = Settings::load(settings_storage.as_ref());
+
+        let themes = theme::list(&mut assets).await.unwrap_or_else(|e| {
+            logging::warning!(Game, "{}, only the default theme is available", e);
+            vec![ThemeInfo { id: DEFAULT_THEME.to_string(), name: "Classic".to_string() }]
+        });
+        let theme = match Theme::load(&mut assets, &settings.theme).await {
+            Ok(theme) => theme,
+            Err(e) if settings.theme != DEFAULT_THEME => {
+                logging::warning!(Game, "{}, using the default theme", e);
+                settings.theme = DEFAULT_THEME.to_string();
+                Theme::load(&mut assets, DEFAULT_THEME).await?
+            }
+            Err(e) => return Err(e),
+        };
+
+        let pipe_shape = match assets.sprite_mask(MASK_SPRITE) {
+            Ok(mask) => PipeShape::Mask(mask),
+            Err(e) => {
+                logging::warning!(Game, "{}, using full rectangles for pipes", e);
+                PipeShape::Rect
+            }
+        };
+        // Replays are verified against the mask, so runs without it are not saved
+        let mut replay_path = platform::arg("record-replay");
+        if matches!(pipe_shape, PipeShape::Rect) && replay_path.take().is_some() {
+            logging::warning!(Game, "Not recording replays without the pipe mask, they would fail verification");
+        }
+
+        // Default sizes whatever the theme, so it never changes the rules or replays.
+        // A replay brings the bird hitbox it was recorded with.
+        let bird_shape = match &replay_playback {
+            Some(playback) => playback.bird_shape(),
+            None => BirdShape::from_launch_options(),
+        };
+        let hitboxes = Hitboxes {
+            bird_shape,
+            pipe_shape,
+            ..Hitboxes::default()
+        };
+        let mut simulation = Simulation::new(hitboxes, seed_mode);
+
+        let theme_id = theme.id.clone();
+        let (world_renderer, player_renderer) = renderers(theme);
+        simulation.world.parallax.set_layers(world_renderer.background_texture_atlas.scrolls());
+
+        let music_player = MusicPlayer::new(&assets.path("music"), 2.0);
+        let sound_effects = SoundEffects::new(&assets)?;
+        let message_sprite = assets.sprite("message")?;
+        let gameover_sprite = assets.sprite("gameover")?;
+        let mut game_state = Self {
+            assets,
+            themes,
+            theme_id,
+            simulation,
             music_player,
             sound_effects,
-            player,
-            world,
-            gameover_texture,
-            input_buffer_space: false,
-            input_buffer_mouse: false,
+            audio_mixer: AudioMixer::default(),
+            world_renderer,
+            player_renderer,
+            message_sprite,
+            gameover_sprite,
+            high_score: HighScore::new(storage::open()),
+            input: Input::new(InputMap::load(storage::open().as_mut())),
+            new_high_score: false,
+            replay_recorder: ReplayRecorder::default(),
+            replay_path,
+            replay_playback,
+            focus_watcher: FocusWatcher::new(),
+            focus_lost: false,
+            pointer_press: None,
+            settings,
+            settings_storage,
+            settings_menu: SettingsMenu::default(),
+            random_bird,
+            debug_overlay: DebugOverlay::new(false),
+            // --debug-overlay (or ?debug-overlay) shows it from the start, for devices without F3
+            debug_overlay_forced: platform::arg("debug-overlay").is_some(),
             debug_input_frame_count: 0,
             debug_fixed_update_count: 0,
-            debug_inputs_detected: Vec::new(),
+        };
+        game_state.apply_settings();
+        game_state.music_player.update(&game_state.audio_mixer);
+        game_state.music_player.play();
+        Ok(game_state)
+    }
+
+    /// Loads the theme picked in the settings once it differs from the one shown.
+    /// Called between frames since loading is async, a theme that fails to load is switched back.
+    pub async fn update_theme(&mut self) {
+        if self.settings.theme == self.theme_id {
+            return;
         }
+
+        match Theme::load(&mut self.assets, &self.settings.theme).await {
+            Ok(theme) => {
+                logging::info!(Game, "Theme: {}", theme.id);
+                self.theme_id = theme.id.clone();
+                (self.world_renderer, self.player_renderer) = renderers(theme);
+                self.simulation.world.parallax.set_layers(self.world_renderer.background_texture_atlas.scrolls());
+                self.apply_settings();
+            }
+            Err(e) => {
+                logging::error!(Game, "{}", e);
+                self.settings.theme = self.theme_id.clone();
+                self.save_settings();
+            }
+        }
+    }
+
+    /// Pushes the current settings to the audio, renderers and overlay
+    fn apply_settings(&mut self) {
+        let settings = &self.settings;
+        self.audio_mixer.master = settings.master_volume;
+        self.audio_mixer.music = settings.music_volume;
+        self.audio_mixer.sfx = settings.sfx_volume;
+        self.audio_mixer.muted = settings.muted;
+        let birds = self.player_renderer.bird_names();
+        self.player_renderer.bird = settings
+            .bird_color
+            .as_ref()
+            .and_then(|name| birds.iter().position(|bird| bird == name))
+            .unwrap_or(self.random_bird % birds.len());
+        self.player_renderer.reduced_motion = settings.reduced_motion;
+        self.world_renderer.reduced_motion = settings.reduced_motion;
+        self.debug_overlay.visible = settings.debug_overlay || self.debug_overlay_forced;
+    }
+
+    fn save_settings(&mut self) {
+        self.settings.save(self.settings_storage.as_mut());
     }
 
     pub fn update_music(&mut self) {
-        self.music_player.play();
-        self.music_player.update();
+        self.music_player.update(&self.audio_mixer);
     }
 
     /// Check inputs every frame (not just during fixed updates)
@@ -71,164 +233,273 @@ impl GameState {
     /// - Buffering ensures inputs are processed even if they occur between updates
     pub fn check_inputs_every_frame(&mut self) {
         self.debug_input_frame_count += 1;
-        
-        // Poll for new input events this frame
-        let space_pressed = is_key_pressed(KeyCode::Space);
-        let mouse_pressed = is_mouse_button_pressed(MouseButton::Left);
-        
-        // Buffer detected inputs (stay true until consumed by fixed update)
-        if space_pressed {
-            self.input_buffer_space = true;
+
+        // Pressed actions stay buffered until consumed by a fixed update
+        let pressed = self.input.poll();
+        if self.focus_watcher.lost_focus() {
+            logging::info!(Scene, "Window lost focus");
+            self.focus_lost = true;
+            // Only a run auto-pauses, the music keeps playing on the other screens
+            if self.simulation.scene == GameScene::Playing {
+                self.music_player.pause();
+            }
         }
-        if mouse_pressed {
-            self.input_buffer_mouse = true;
+
+        // Back from another window or tab, the run stays paused but the music can come back
+        if !pressed.is_empty() && self.music_player.state() == MusicState::Paused {
+            self.music_player.resume();
         }
-        
-        if space_pressed || mouse_pressed {
-            let input_type = if space_pressed && mouse_pressed {
-                "SPACE+MOUSE"
-            } else if space_pressed {
-                "SPACE"
-            } else {
-                "MOUSE"
-            };
-            
-            let msg = format!(
-                "[INPUT-DETECTED] Frame {}: {} pressed (Scene: {:?}) - BUFFERED",
-                self.debug_input_frame_count, input_type, self.scene
+
+        // Mute right away instead of waiting for the next fixed update
+        if pressed.contains(&Action::Mute) {
+            self.settings.muted = !self.settings.muted;
+            logging::info!(Audio, "Muted: {}", self.settings.muted);
+            self.apply_settings();
+            self.save_settings();
+        }
+        if pressed.contains(&Action::DebugOverlay) {
+            self.settings.debug_overlay = !self.debug_overlay.visible;
+            self.debug_overlay_forced = false;
+            self.apply_settings();
+            self.save_settings();
+        }
+
+        // Menus are clicked or tapped wherever the flap bindings are
+        let touch = touches().into_iter().find(|touch| touch.phase == TouchPhase::Started);
+        if let Some(touch) = touch {
+            self.pointer_press = Some(touch.position);
+        } else if is_mouse_button_pressed(MouseButton::Left) {
+            self.pointer_press = Some(mouse_position().into());
+        }
+
+        if !pressed.is_empty() {
+            self.debug_overlay.record_inputs(self.debug_input_frame_count, &pressed);
+            logging::debug!(
+                Input,
+                "Frame {}: {:?} pressed (Scene: {:?}) - buffered",
+                self.debug_input_frame_count, pressed, self.simulation.scene
             );
-            println!("{}", msg);
-            self.debug_inputs_detected.push(msg);
-            
-            // Keep only last 10 inputs for debugging
-            if self.debug_inputs_detected.len() > 10 {
-                self.debug_inputs_detected.remove(0);
-            }
         }
     }
 
-    fn handle_input(&mut self) -> (bool, bool) {
+    /// Timing of the frame just simulated, for the debug overlay
+    pub fn record_frame(&mut self, stats: FrameStats) {
+        self.debug_overlay.record_frame(stats);
+    }
+
+    fn handle_input(&mut self) -> bool {
         self.debug_fixed_update_count += 1;
-        
-        // Read buffered inputs from check_inputs_every_frame()
+
+        // Read buffered actions from check_inputs_every_frame()
         // Buffers cleared at end of update() to ensure they're processed exactly once
-        let space_pressed = self.input_buffer_space;
-        let mouse_pressed = self.input_buffer_mouse;
-        
-        if space_pressed || mouse_pressed {
-            let input_type = if space_pressed && mouse_pressed {
-                "SPACE+MOUSE"
-            } else if space_pressed {
-                "SPACE"
-            } else {
-                "MOUSE"
-            };
-            
-            println!(
-                "[INPUT-PROCESSED] Fixed update {}: {} processed from buffer (Scene: {:?}, Frame: {})",
-                self.debug_fixed_update_count, input_type, self.scene, self.debug_input_frame_count
+        let flap = self.input.pressed(Action::Flap);
+        // Confirm also starts a run or leaves the game over screen, but never flaps mid-run
+        let confirm = self.input.pressed(Action::Confirm) && self.simulation.scene != GameScene::Playing;
+
+        if flap || confirm {
+            logging::trace!(
+                Input,
+                "Fixed update {}: {} processed from buffer (Scene: {:?}, Frame: {})",
+                self.debug_fixed_update_count,
+                if flap { "Flap" } else { "Confirm" },
+                self.simulation.scene,
+                self.debug_input_frame_count
             );
         }
-        
-        match self.scene {
+
+        flap || confirm
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        // Get buffered input state (used for scene changes and jump)
+        let live_flap = self.handle_input();
+
+        let mut events = Vec::new();
+        let mut live_flap = live_flap;
+        match self.simulation.scene {
             GameScene::StartScreen => {
-                if space_pressed || mouse_pressed {
-                    println!("[SCENE-CHANGE] StartScreen -> Playing");
-                    self.scene = GameScene::Playing;
+                let button = self.pointer_press.is_some_and(|pointer| settings_menu::OPEN_BUTTON.contains(pointer));
+                if button || self.input.pressed(Action::Settings) {
+                    events.extend(self.simulation.open_settings());
+                    // The click on the button must not start a run
+                    live_flap = false;
                 }
             }
-            GameScene::Playing => {
-            }
-            GameScene::GameOver => {
-                if space_pressed || mouse_pressed {
-                    println!("[SCENE-CHANGE] GameOver -> StartScreen");
-                    self.scene = GameScene::StartScreen;
-                    self.music_player.next();
+            GameScene::Settings => {
+                let birds = self.player_renderer.bird_names();
+                let choices = Choices { themes: &self.themes, birds: &birds };
+                let debug_overlay = self.settings.debug_overlay;
+                match self.settings_menu.update(&self.input, self.pointer_press, &mut self.settings, &choices) {
+                    MenuOutcome::None => {}
+                    MenuOutcome::Changed => {
+                        // Toggling the setting takes over from --debug-overlay
+                        if self.settings.debug_overlay != debug_overlay {
+                            self.debug_overlay_forced = false;
+                        }
+                        self.apply_settings();
+                    }
+                    MenuOutcome::Close => {
+                        self.save_settings();
+                        events.extend(self.simulation.close_settings());
+                    }
                 }
+                live_flap = false;
             }
+            GameScene::Playing | GameScene::Paused | GameScene::GameOver => {}
         }
-        
-        (space_pressed, mouse_pressed)
-    }
 
-    pub fn update(&mut self, dt: f32) {
-        // Get buffered input state (used for scene changes and jump)
-        let (space_pressed, mouse_pressed) = self.handle_input();
-        let player  = &mut self.player;
-        let world = &mut self.world;
+        // Losing focus always pauses, Pause toggles and Back or a flap also resume
+        let paused = self.simulation.scene == GameScene::Paused;
+        let toggle_pause = self.input.pressed(Action::Pause);
+        if self.focus_lost || (toggle_pause && !paused) {
+            events.extend(self.simulation.pause());
+        } else if paused && (toggle_pause || self.input.pressed(Action::Back) || live_flap) {
+            self.simulation.resume();
+        }
 
-        match self.scene {
-            GameScene::StartScreen => {
-                player.restart();
-                world.restart();
-            }
-            GameScene::Playing => {
-                // Handle jump input from buffer (before physics update)
-                if space_pressed || mouse_pressed {
-                    player.handle_jump();
-                    println!(
-                        "[WING-SOUND] Fixed update {}: Wing sound triggered (Frame: {})",
+        // A replay being played back replaces live input until its run ends.
+        // Paused steps keep the same tick, so playback waits for the run to continue.
+        let tick = self.simulation.tick;
+        let waiting = matches!(self.simulation.scene, GameScene::Paused | GameScene::Settings);
+        let flap = match &mut self.replay_playback {
+            _ if waiting => false,
+            Some(playback) => playback.flap_at(tick),
+            None => live_flap,
+        };
+
+        events.extend(self.simulation.step(dt, flap));
+
+        if let Some(replay) = self.replay_recorder.record(&self.simulation, tick, flap, &events) {
+            self.save_replay(&replay);
+        }
+
+        for event in events {
+            match event {
+                SimEvent::Flapped => {
+                    logging::trace!(
+                        Audio,
+                        "Fixed update {}: Wing sound triggered (Frame: {})",
                         self.debug_fixed_update_count, self.debug_input_frame_count
                     );
-                    self.sound_effects.play_wing();
+                    self.sound_effects.play_wing(&self.audio_mixer);
                 }
-
-                player.update(dt);
-                world.update(dt);
-
-                // Play sound when score increases
-                if world.player_passed_pipes(player) {
-                    self.sound_effects.play_point();
+                SimEvent::Scored => {
+                    // Play sound when score increases
+                    self.sound_effects.play_point(&self.audio_mixer);
                 }
-
-                if world.touched(player) {
-                    self.sound_effects.play_hit();
-                    player.dead();
-                    world.end();
+                SimEvent::Died => {
+                    if let Some(playback) = self.replay_playback.take() {
+                        let (score, death_tick) = playback.expected();
+                        logging::info!(
+                            Game,
+                            "Replay finished: score {} at tick {} (recorded: score {} at tick {})",
+                            self.simulation.world.score, tick, score, death_tick
+                        );
+                        self.new_high_score = false;
+                    } else {
+                        self.new_high_score = self.high_score.submit(self.simulation.world.score);
+                    }
+                    self.sound_effects.play_hit(&self.audio_mixer);
                     self.music_player.stop();
-                    self.scene = GameScene::GameOver;
-                    self.sound_effects.play_death();
+                    self.sound_effects.play_death(&self.audio_mixer);
                 }
-            }
-            GameScene::GameOver => {
-                
-                player.update(dt);
+                SimEvent::SceneChanged { from: GameScene::GameOver, to: GameScene::StartScreen } => {
+                    self.music_player.next();
+                }
+                SimEvent::SceneChanged { to: GameScene::Paused, .. } => {
+                    self.audio_mixer.ducked = true;
+                }
+                SimEvent::SceneChanged { from: GameScene::Paused, .. } => {
+                    self.audio_mixer.ducked = false;
+                    self.music_player.resume();
+                }
+                SimEvent::SceneChanged { .. } => {}
             }
         }
         
         // Clear input buffers after processing (prevents repeated jumps from single press)
-        self.input_buffer_space = false;
-        self.input_buffer_mouse = false;
+        self.input.clear();
+        self.pointer_press = None;
+        self.focus_lost = false;
     }
 
-    pub fn draw(&mut self, message: &Texture2D, _alpha: f32) {
-        match self.scene {
+    fn save_replay(&self, replay: &Replay) {
+        let Some(path) = &self.replay_path else {
+            return;
+        };
+        let path = if path.is_empty() {
+            format!("replay-{}.fbr", replay.seed)
+        } else {
+            path.clone()
+        };
+
+        match replay.save(&path) {
+            Ok(()) => logging::info!(Game, "Saved replay (score {}) to {}", replay.score, path),
+            Err(e) => logging::error!(Game, "{}", e),
+        }
+    }
+
+    pub fn draw(&mut self, alpha: f32) {
+        match self.simulation.scene {
             GameScene::StartScreen => {
-                self.world.draw();
+                let message = &self.message_sprite;
+                self.world_renderer.draw(&self.simulation.world, alpha);
                 let msg_x = (SCREEN_WIDTH - message.width()) / 2.0;
                 let msg_y = (SCREEN_HEIGHT - message.height()) / 2.0 - 50.0;
-                draw_texture(message, msg_x, msg_y, WHITE);
+                message.draw(msg_x, msg_y, DrawTextureParams::default());
+                // The bird idles over the one in the message
+                self.player_renderer.draw(&self.simulation.player, alpha);
+                settings_menu::draw_open_button();
+            }
+            GameScene::Settings => {
+                self.world_renderer.draw(&self.simulation.world, 1.0);
+                let birds = self.player_renderer.bird_names();
+                self.settings_menu.draw(&self.settings, &Choices { themes: &self.themes, birds: &birds });
             }
             GameScene::Playing => {
-                self.world.draw();
-                self.player.draw();
+                self.world_renderer.draw(&self.simulation.world, alpha);
+                self.player_renderer.draw(&self.simulation.player, alpha);
+
+            }
+            GameScene::Paused => {
+                // Nothing moves while paused, draw the last step as is
+                self.world_renderer.draw(&self.simulation.world, 1.0);
+                self.player_renderer.draw(&self.simulation.player, 1.0);
+                draw_rectangle(0.0, 0.0, SCREEN_WIDTH, SCREEN_HEIGHT, Color::new(0.0, 0.0, 0.0, 0.5));
+
+                let center_y = SCREEN_HEIGHT / 2.0 - 50.0;
+                let (title, title_size, hint) = match self.simulation.resume_countdown {
+                    Some(remaining) => (format!("{}", remaining.ceil() as u32), 64.0, "Get ready".to_string()),
+                    None => {
+                        let keys = self.input.map().hint(&[Action::Pause, Action::Flap]);
+                        ("Paused".to_string(), 40.0, format!("Press {} to resume", keys))
+                    }
+                };
+
+                let title_dimensions = measure_text(&title, None, title_size as u16, 1.0);
+                let title_x = (SCREEN_WIDTH - title_dimensions.width) / 2.0;
+                draw_text(&title, title_x, center_y, title_size, WHITE);
 
+                let hint_dimensions = measure_text(&hint, None, 18, 1.0);
+                let hint_x = (SCREEN_WIDTH - hint_dimensions.width) / 2.0;
+                draw_text(&hint, hint_x, center_y + 40.0, 18.0, WHITE);
             }
             GameScene::GameOver => {
                 // Draw game over screen
-                self.world.draw();
-                self.player.draw();
+                self.world_renderer.draw(&self.simulation.world, alpha);
+                self.player_renderer.draw(&self.simulation.player, alpha);
 
                 // Draw gameover image centered
-                let gameover_x = (SCREEN_WIDTH - self.gameover_texture.width()) / 2.0;
-                let gameover_y = (SCREEN_HEIGHT - self.gameover_texture.height()) / 2.0 - 100.0;
-                draw_texture(&self.gameover_texture, gameover_x, gameover_y, WHITE);
+                let gameover_sprite = &self.gameover_sprite;
+                let gameover_x = (SCREEN_WIDTH - gameover_sprite.width()) / 2.0;
+                let gameover_y = (SCREEN_HEIGHT - gameover_sprite.height()) / 2.0 - 100.0;
+                gameover_sprite.draw(gameover_x, gameover_y, DrawTextureParams::default());
 
                 // Draw instructions (two lines)
                 let font_size = 20.0;
                 let line_spacing = 25.0;
 
-                let line1 = "Press SPACE or click";
+                let line1 = &format!("Press {}", self.input.map().hint(&[Action::Flap]));
                 let line2 = "to continue";
 
                 let line1_dimensions = measure_text(line1, None, font_size as u16, 1.0);
@@ -236,12 +507,48 @@ impl GameState {
 
                 let line1_x = (SCREEN_WIDTH - line1_dimensions.width) / 2.0;
                 let line2_x = (SCREEN_WIDTH - line2_dimensions.width) / 2.0;
-                let start_y = gameover_y + self.gameover_texture.height() + 50.0;
+                let scores_y = gameover_y + gameover_sprite.height() + 40.0;
+                let start_y = scores_y + 60.0;
+
+                // Current and best score side by side
+                let scores = format!(
+                    "Score {}    Best {}",
+                    self.simulation.world.score, self.high_score.best
+                );
+                let scores_dimensions = measure_text(&scores, None, 24, 1.0);
+                let scores_x = (SCREEN_WIDTH - scores_dimensions.width) / 2.0;
+                draw_text(&scores, scores_x, scores_y, 24.0, WHITE);
+
+                if self.new_high_score {
+                    let new_best = "New best!";
+                    let new_best_dimensions = measure_text(new_best, None, font_size as u16, 1.0);
+                    let new_best_x = (SCREEN_WIDTH - new_best_dimensions.width) / 2.0;
+                    draw_text(new_best, new_best_x, scores_y + line_spacing, font_size, GOLD);
+                }
 
                 draw_text(line1, line1_x, start_y, font_size, WHITE);
                 draw_text(line2, line2_x, start_y + line_spacing, font_size, WHITE);
+
+                // Seed of the run, so it can be replayed with --seed / ?seed=
+                let seed_text = format!("seed {}", self.simulation.seed);
+                let seed_dimensions = measure_text(&seed_text, None, 14, 1.0);
+                let seed_x = (SCREEN_WIDTH - seed_dimensions.width) / 2.0;
+                draw_text(&seed_text, seed_x, start_y + line_spacing * 2.0, 14.0, WHITE);
             }
         }
+
+        self.debug_overlay.draw(&self.simulation, self.debug_input_frame_count, self.debug_fixed_update_count);
     }
 
-}
\ No newline at end of file
+}
+
+/// World and bird renderers drawing with a theme's sprites
+fn renderers(theme: Theme) -> (WorldRenderer, PlayerRenderer) {
+    let world_renderer = WorldRenderer::new(
+        theme.backgrounds,
+        theme.digits,
+        PipeRenderer::new(theme.pipes),
+        BaseRenderer::new(theme.base),
+    );
+    (world_renderer, PlayerRenderer::new(theme.birds))
+}
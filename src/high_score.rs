@@ -0,0 +1,34 @@
+use crate::storage::Storage;
+use crate::logging;
+
+const KEY: &str = "high_score";
+
+/// Best score across sessions, saved whenever it is beaten
+pub struct HighScore {
+    storage: Box<dyn Storage>,
+    pub best: u32,
+}
+
+impl HighScore {
+    pub fn new(storage: Box<dyn Storage>) -> Self {
+        let best = storage
+            .get(KEY)
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(0);
+
+        HighScore { storage, best }
+    }
+
+    /// Returns true when `score` is a new best
+    pub fn submit(&mut self, score: u32) -> bool {
+        if score <= self.best {
+            return false;
+        }
+
+        self.best = score;
+        if let Err(e) = self.storage.set(KEY, &score.to_string()) {
+            logging::warning!(Game, "{}", e);
+        }
+        true
+    }
+}
@@ -0,0 +1,438 @@
+//! Maps keys, mouse, touch and gamepad buttons to game actions.
+//! Presses are polled every frame and stay buffered until the next fixed update consumes them,
+//! so a quick tap between two updates is never lost and never counted twice.
+
+use macroquad::input::{
+    is_key_pressed, is_mouse_button_pressed, touches, KeyCode, MouseButton, TouchPhase,
+};
+use crate::platform;
+use crate::storage::Storage;
+use crate::logging;
+
+const KEY: &str = "bindings";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Flap,
+    Pause,
+    Confirm,
+    Back,
+    Mute,
+    /// Shows the timing and hitbox overlay
+    DebugOverlay,
+    /// Opens the settings menu from the start screen
+    Settings,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::Flap,
+        Action::Pause,
+        Action::Confirm,
+        Action::Back,
+        Action::Mute,
+        Action::DebugOverlay,
+        Action::Settings,
+        Action::MenuUp,
+        Action::MenuDown,
+        Action::MenuLeft,
+        Action::MenuRight,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Flap => "flap",
+            Action::Pause => "pause",
+            Action::Confirm => "confirm",
+            Action::Back => "back",
+            Action::Mute => "mute",
+            Action::DebugOverlay => "debug",
+            Action::Settings => "settings",
+            Action::MenuUp => "up",
+            Action::MenuDown => "down",
+            Action::MenuLeft => "left",
+            Action::MenuRight => "right",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Buttons of the browser's "standard" gamepad layout, named after an Xbox pad
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadButton {
+    A = 0,
+    B = 1,
+    X = 2,
+    Y = 3,
+    LeftBumper = 4,
+    RightBumper = 5,
+    LeftTrigger = 6,
+    RightTrigger = 7,
+    Select = 8,
+    Start = 9,
+    Up = 12,
+    Down = 13,
+    Left = 14,
+    Right = 15,
+}
+
+const GAMEPAD_BUTTONS: [(&str, GamepadButton); 14] = [
+    ("A", GamepadButton::A),
+    ("B", GamepadButton::B),
+    ("X", GamepadButton::X),
+    ("Y", GamepadButton::Y),
+    ("LeftBumper", GamepadButton::LeftBumper),
+    ("RightBumper", GamepadButton::RightBumper),
+    ("LeftTrigger", GamepadButton::LeftTrigger),
+    ("RightTrigger", GamepadButton::RightTrigger),
+    ("Select", GamepadButton::Select),
+    ("Start", GamepadButton::Start),
+    ("Up", GamepadButton::Up),
+    ("Down", GamepadButton::Down),
+    ("Left", GamepadButton::Left),
+    ("Right", GamepadButton::Right),
+];
+
+const MOUSE_BUTTONS: [(&str, MouseButton); 3] = [
+    ("Left", MouseButton::Left),
+    ("Right", MouseButton::Right),
+    ("Middle", MouseButton::Middle),
+];
+
+/// Keys that can be named in the bindings file
+const KEYS: [(&str, KeyCode); 62] = [
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+    ("KpEnter", KeyCode::KpEnter),
+    ("Escape", KeyCode::Escape),
+    ("Backspace", KeyCode::Backspace),
+    ("Tab", KeyCode::Tab),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("LeftShift", KeyCode::LeftShift),
+    ("RightShift", KeyCode::RightShift),
+    ("LeftControl", KeyCode::LeftControl),
+    ("RightControl", KeyCode::RightControl),
+    ("LeftAlt", KeyCode::LeftAlt),
+    ("RightAlt", KeyCode::RightAlt),
+    ("A", KeyCode::A),
+    ("B", KeyCode::B),
+    ("C", KeyCode::C),
+    ("D", KeyCode::D),
+    ("E", KeyCode::E),
+    ("F", KeyCode::F),
+    ("G", KeyCode::G),
+    ("H", KeyCode::H),
+    ("I", KeyCode::I),
+    ("J", KeyCode::J),
+    ("K", KeyCode::K),
+    ("L", KeyCode::L),
+    ("M", KeyCode::M),
+    ("N", KeyCode::N),
+    ("O", KeyCode::O),
+    ("P", KeyCode::P),
+    ("Q", KeyCode::Q),
+    ("R", KeyCode::R),
+    ("S", KeyCode::S),
+    ("T", KeyCode::T),
+    ("U", KeyCode::U),
+    ("V", KeyCode::V),
+    ("W", KeyCode::W),
+    ("X", KeyCode::X),
+    ("Y", KeyCode::Y),
+    ("Z", KeyCode::Z),
+    ("0", KeyCode::Key0),
+    ("1", KeyCode::Key1),
+    ("2", KeyCode::Key2),
+    ("3", KeyCode::Key3),
+    ("4", KeyCode::Key4),
+    ("5", KeyCode::Key5),
+    ("6", KeyCode::Key6),
+    ("7", KeyCode::Key7),
+    ("8", KeyCode::Key8),
+    ("9", KeyCode::Key9),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+];
+
+/// One physical input, written as `key:Space`, `mouse:Left`, `touch` or `pad:Start`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// Any new touch on the screen
+    Touch,
+    /// First connected gamepad, only available in the browser for now
+    Gamepad(GamepadButton),
+}
+
+impl Binding {
+    pub fn from_name(name: &str) -> Option<Self> {
+        fn find<T: Copy>(table: &[(&str, T)], name: &str) -> Option<T> {
+            table.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|&(_, value)| value)
+        }
+
+        match name.split_once(':') {
+            Some(("key", key)) => find(&KEYS, key).map(Binding::Key),
+            Some(("mouse", button)) => find(&MOUSE_BUTTONS, button).map(Binding::Mouse),
+            Some(("pad", button)) => find(&GAMEPAD_BUTTONS, button).map(Binding::Gamepad),
+            None if name == "touch" => Some(Binding::Touch),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("key:{}", find(&KEYS, key)),
+            Binding::Mouse(button) => format!("mouse:{}", find(&MOUSE_BUTTONS, button)),
+            Binding::Touch => "touch".to_string(),
+            Binding::Gamepad(button) => format!("pad:{}", find(&GAMEPAD_BUTTONS, button)),
+        }
+    }
+
+    /// Short name for on-screen hints, e.g. `SPACE`, `click` or `tap`
+    pub fn label(&self) -> String {
+        match self {
+            Binding::Key(KeyCode::Escape) => "ESC".to_string(),
+            Binding::Key(key) => find(&KEYS, key).to_uppercase(),
+            Binding::Mouse(MouseButton::Left) => "click".to_string(),
+            Binding::Mouse(button) => format!("{} click", find(&MOUSE_BUTTONS, button).to_lowercase()),
+            Binding::Touch => "tap".to_string(),
+            Binding::Gamepad(button) => format!("pad {}", find(&GAMEPAD_BUTTONS, button)),
+        }
+    }
+}
+
+/// Name of `value` in one of the binding tables
+fn find<T: PartialEq>(table: &[(&'static str, T)], value: &T) -> &'static str {
+    table.iter().find(|(_, v)| v == value).map_or("?", |&(name, _)| name)
+}
+
+/// Which bindings trigger each action. One binding may trigger several actions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputMap {
+    bindings: Vec<(Action, Binding)>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use Binding::*;
+
+        InputMap {
+            bindings: vec![
+                (Action::Flap, Key(KeyCode::Space)),
+                (Action::Flap, Mouse(MouseButton::Left)),
+                (Action::Flap, Touch),
+                (Action::Flap, Gamepad(GamepadButton::A)),
+                (Action::Pause, Key(KeyCode::P)),
+                (Action::Pause, Key(KeyCode::Escape)),
+                (Action::Pause, Gamepad(GamepadButton::Start)),
+                (Action::Confirm, Key(KeyCode::Enter)),
+                (Action::Confirm, Key(KeyCode::KpEnter)),
+                (Action::Confirm, Gamepad(GamepadButton::A)),
+                (Action::Back, Key(KeyCode::Backspace)),
+                (Action::Back, Key(KeyCode::Escape)),
+                (Action::Back, Gamepad(GamepadButton::B)),
+                (Action::Mute, Key(KeyCode::M)),
+                (Action::Mute, Gamepad(GamepadButton::Select)),
+                (Action::DebugOverlay, Key(KeyCode::F3)),
+                (Action::Settings, Key(KeyCode::O)),
+                (Action::Settings, Key(KeyCode::Tab)),
+                (Action::Settings, Gamepad(GamepadButton::Y)),
+                (Action::MenuUp, Key(KeyCode::Up)),
+                (Action::MenuUp, Key(KeyCode::W)),
+                (Action::MenuUp, Gamepad(GamepadButton::Up)),
+                (Action::MenuDown, Key(KeyCode::Down)),
+                (Action::MenuDown, Key(KeyCode::S)),
+                (Action::MenuDown, Gamepad(GamepadButton::Down)),
+                (Action::MenuLeft, Key(KeyCode::Left)),
+                (Action::MenuLeft, Key(KeyCode::A)),
+                (Action::MenuLeft, Gamepad(GamepadButton::Left)),
+                (Action::MenuRight, Key(KeyCode::Right)),
+                (Action::MenuRight, Key(KeyCode::D)),
+                (Action::MenuRight, Gamepad(GamepadButton::Right)),
+            ],
+        }
+    }
+}
+
+impl InputMap {
+    /// One line per action, e.g. `flap = key:Space, mouse:Left, touch, pad:A`.
+    /// Actions left out keep their default bindings, `#` starts a comment.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut map = InputMap::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (action, bindings) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Line {}: expected `action = bindings`", number + 1))?;
+            let action = Action::from_name(action.trim())
+                .ok_or_else(|| format!("Line {}: unknown action {}", number + 1, action.trim()))?;
+
+            map.bindings.retain(|&(a, _)| a != action);
+            for name in bindings.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+                let binding = Binding::from_name(name)
+                    .ok_or_else(|| format!("Line {}: unknown binding {}", number + 1, name))?;
+                map.bindings.push((action, binding));
+            }
+        }
+
+        Ok(map)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for action in Action::ALL {
+            let names: Vec<String> = self.bindings_for(action).map(|binding| binding.name()).collect();
+            text.push_str(&format!("{} = {}\n", action.name(), names.join(", ")));
+        }
+        text
+    }
+
+    /// Reads the `bindings` entry, writing the defaults there first so there is a file to edit
+    pub fn load(storage: &mut dyn Storage) -> Self {
+        match storage.get(KEY) {
+            Some(text) => match Self::parse(&text) {
+                Ok(map) => map,
+                Err(e) => {
+                    logging::warning!(Input, "Invalid bindings, using defaults: {}", e);
+                    Self::default()
+                }
+            },
+            None => {
+                let map = Self::default();
+                if let Err(e) = storage.set(KEY, &map.to_text()) {
+                    logging::warning!(Game, "{}", e);
+                }
+                map
+            }
+        }
+    }
+
+    /// What to press for any of `actions`, e.g. `P, ESC or SPACE`.
+    /// Keys and mouse buttons only, to keep it short, unless nothing else is bound.
+    pub fn hint(&self, actions: &[Action]) -> String {
+        let bindings: Vec<&Binding> = actions.iter().flat_map(|&action| self.bindings_for(action)).collect();
+        let desktop: Vec<&Binding> = bindings
+            .iter()
+            .copied()
+            .filter(|binding| matches!(binding, Binding::Key(_) | Binding::Mouse(_)))
+            .collect();
+        let shown = if desktop.is_empty() { bindings } else { desktop };
+
+        let mut labels: Vec<String> = Vec::new();
+        for label in shown.iter().map(|binding| binding.label()) {
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+        match labels.split_last() {
+            None => "?".to_string(),
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        }
+    }
+
+    pub fn bindings_for(&self, action: Action) -> impl Iterator<Item = &Binding> {
+        self.bindings
+            .iter()
+            .filter(move |&&(a, _)| a == action)
+            .map(|(_, binding)| binding)
+    }
+}
+
+/// Polls the bindings of an `InputMap` and buffers which actions were triggered
+pub struct Input {
+    map: InputMap,
+    buffered: [bool; Action::ALL.len()],
+    gamepad_buttons: u32,
+}
+
+impl Input {
+    pub fn new(map: InputMap) -> Self {
+        Input {
+            map,
+            buffered: [false; Action::ALL.len()],
+            gamepad_buttons: 0,
+        }
+    }
+
+    /// Call once per frame. Returns the actions that were newly pressed this frame.
+    pub fn poll(&mut self) -> Vec<Action> {
+        let gamepad_buttons = platform::gamepad_buttons();
+        let gamepad_pressed = gamepad_buttons & !self.gamepad_buttons;
+        self.gamepad_buttons = gamepad_buttons;
+        let touched = touches().iter().any(|touch| touch.phase == TouchPhase::Started);
+
+        let mut pressed = Vec::new();
+        for &(action, binding) in &self.map.bindings {
+            let down = match binding {
+                Binding::Key(key) => is_key_pressed(key),
+                Binding::Mouse(button) => is_mouse_button_pressed(button),
+                Binding::Touch => touched,
+                Binding::Gamepad(button) => gamepad_pressed & (1 << button as u32) != 0,
+            };
+            if down && !pressed.contains(&action) {
+                pressed.push(action);
+                self.buffered[action.index()] = true;
+            }
+        }
+        pressed
+    }
+
+    /// Whether `action` was pressed since the last `clear`
+    pub fn map(&self) -> &InputMap {
+        &self.map
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.buffered[action.index()]
+    }
+
+    /// Call after each fixed update so every press is handled exactly once
+    pub fn clear(&mut self) {
+        self.buffered = [false; Action::ALL.len()];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hints_follow_the_bindings() {
+        let map = InputMap::default();
+        assert_eq!(map.hint(&[Action::Flap]), "SPACE or click");
+        assert_eq!(map.hint(&[Action::Pause, Action::Flap]), "P, ESC, SPACE or click");
+
+        let map = InputMap::parse("flap = key:W, touch\npause = pad:Start").unwrap();
+        assert_eq!(map.hint(&[Action::Flap]), "W");
+        assert_eq!(map.hint(&[Action::Pause]), "pad Start");
+    }
+}
@@ -0,0 +1,59 @@
+use macroquad::prelude::*;
+
+const FONT_SIZE: f32 = 30.0;
+const BAR_WIDTH: f32 = 220.0;
+const BAR_HEIGHT: f32 = 12.0;
+
+/// Progress bar with a spinner that keeps turning while a slow file downloads
+pub fn draw_loading_screen(loaded: usize, total: usize) {
+    let center_x = screen_width() / 2.0;
+    let center_y = screen_height() / 2.0;
+
+    let text = "Loading assets...";
+    let text_dims = measure_text(text, None, FONT_SIZE as u16, 1.0);
+    draw_text(text, center_x - text_dims.width / 2.0, center_y, FONT_SIZE, WHITE);
+
+    let bar_x = center_x - BAR_WIDTH / 2.0;
+    let bar_y = center_y + 20.0;
+    let progress = if total > 0 { loaded as f32 / total as f32 } else { 1.0 };
+    draw_rectangle(bar_x, bar_y, BAR_WIDTH * progress, BAR_HEIGHT, WHITE);
+    draw_rectangle_lines(bar_x, bar_y, BAR_WIDTH, BAR_HEIGHT, 2.0, WHITE);
+
+    let count = format!("{} / {}", loaded, total);
+    let count_dims = measure_text(&count, None, 18, 1.0);
+    draw_text(&count, center_x - count_dims.width / 2.0, bar_y + BAR_HEIGHT + 20.0, 18.0, GRAY);
+
+    // One bright dot circling the others
+    let spinner_radius = 20.0;
+    let spinner_y = bar_y + BAR_HEIGHT + 60.0;
+    let lit = (get_time() * 10.0) as usize % 8;
+    for i in 0..8 {
+        let angle = i as f32 * std::f32::consts::PI / 4.0;
+        let x = center_x + angle.cos() * spinner_radius;
+        let y = spinner_y + angle.sin() * spinner_radius;
+        let color = if i == lit { WHITE } else { DARKGRAY };
+        draw_circle(x, y, 3.0, color);
+    }
+}
+
+/// Shown instead of the game when loading failed, wrapped to fit the screen
+pub fn draw_error_screen(message: &str) {
+    let title = "Failed to start";
+    let title_dims = measure_text(title, None, FONT_SIZE as u16, 1.0);
+    draw_text(title, (screen_width() - title_dims.width) / 2.0, 120.0, FONT_SIZE, RED);
+
+    let max_width = screen_width() - 20.0;
+    let mut y = 160.0;
+    let mut line = String::new();
+    for word in message.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+        if !line.is_empty() && measure_text(&candidate, None, 18, 1.0).width > max_width {
+            draw_text(&line, 10.0, y, 18.0, WHITE);
+            y += 22.0;
+            line = word.to_string();
+        } else {
+            line = candidate;
+        }
+    }
+    draw_text(&line, 10.0, y, 18.0, WHITE);
+}
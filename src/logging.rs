@@ -0,0 +1,178 @@
+//! Leveled logging split by subsystem.
+//! Configured with `FLAPPY_LOG` (or `--log`, `?log=` on the web), e.g. `FLAPPY_LOG=info,input=trace,timing=off`.
+//! Debug builds log `info` and up by default, release builds stay silent.
+//! Native builds write to stderr, the web build to the browser console.
+
+use std::fmt;
+use std::sync::OnceLock;
+use crate::platform;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Input,
+    Scene,
+    Physics,
+    Audio,
+    Timing,
+    /// Seeds, replays, storage and assets
+    Game,
+}
+
+impl Target {
+    const ALL: [Target; 6] = [Target::Input, Target::Scene, Target::Physics, Target::Audio, Target::Timing, Target::Game];
+
+    fn name(self) -> &'static str {
+        match self {
+            Target::Input => "input",
+            Target::Scene => "scene",
+            Target::Physics => "physics",
+            Target::Audio => "audio",
+            Target::Timing => "timing",
+            Target::Game => "game",
+        }
+    }
+}
+
+/// Highest level shown per target, `None` turns a target off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Filter {
+    levels: [Option<Level>; Target::ALL.len()],
+}
+
+impl Filter {
+    fn default_for_build() -> Self {
+        let level = if cfg!(debug_assertions) { Some(Level::Info) } else { None };
+        Filter { levels: [level; Target::ALL.len()] }
+    }
+
+    /// Comma separated `level` or `target=level` entries, later entries win
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter = Self::default_for_build();
+
+        for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (target, level) = match entry.split_once('=') {
+                Some((target, level)) => (Some(target.trim()), level.trim()),
+                None => (None, entry),
+            };
+            let level = match level {
+                "off" => None,
+                name => Some(Level::from_name(name).ok_or_else(|| format!("Unknown log level {}", name))?),
+            };
+
+            match target {
+                Some(name) => {
+                    let index = Target::ALL
+                        .iter()
+                        .position(|target| target.name() == name)
+                        .ok_or_else(|| format!("Unknown log target {}", name))?;
+                    filter.levels[index] = level;
+                }
+                None => filter.levels = [level; Target::ALL.len()],
+            }
+        }
+        Ok(filter)
+    }
+}
+
+static FILTER: OnceLock<Filter> = OnceLock::new();
+
+/// Reads the log configuration, call once at startup
+pub fn init() {
+    let spec = platform::arg("log");
+    #[cfg(not(target_arch = "wasm32"))]
+    let spec = spec.or_else(|| std::env::var("FLAPPY_LOG").ok());
+
+    let (filter, error) = match spec.map(|spec| Filter::parse(&spec)) {
+        Some(Ok(filter)) => (filter, None),
+        Some(Err(e)) => (Filter::default_for_build(), Some(e)),
+        None => (Filter::default_for_build(), None),
+    };
+    let _ = FILTER.set(filter);
+
+    if let Some(e) = error {
+        write(Level::Warn, Target::Game, format_args!("Invalid log configuration: {}", e));
+    }
+}
+
+pub fn enabled(level: Level, target: Target) -> bool {
+    let filter = FILTER.get_or_init(Filter::default_for_build);
+    filter.levels[target as usize].is_some_and(|max| level <= max)
+}
+
+pub fn write(level: Level, target: Target, args: fmt::Arguments) {
+    let message = format!("[{} {}] {}", level.name(), target.name(), args);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{}", message);
+
+    #[cfg(target_arch = "wasm32")]
+    platform::console_log(level as u32, &message);
+}
+
+/// `log!(Info, Scene, "...")`, usually through the level macros below
+macro_rules! log {
+    ($level:ident, $target:ident, $($arg:tt)+) => {
+        if $crate::logging::enabled($crate::logging::Level::$level, $crate::logging::Target::$target) {
+            $crate::logging::write(
+                $crate::logging::Level::$level,
+                $crate::logging::Target::$target,
+                format_args!($($arg)+),
+            );
+        }
+    };
+}
+
+macro_rules! error {
+    ($target:ident, $($arg:tt)+) => { $crate::logging::log!(Error, $target, $($arg)+) };
+}
+
+// Not `warn`, that name clashes with the built-in attribute
+macro_rules! warning {
+    ($target:ident, $($arg:tt)+) => { $crate::logging::log!(Warn, $target, $($arg)+) };
+}
+
+macro_rules! info {
+    ($target:ident, $($arg:tt)+) => { $crate::logging::log!(Info, $target, $($arg)+) };
+}
+
+macro_rules! debug {
+    ($target:ident, $($arg:tt)+) => { $crate::logging::log!(Debug, $target, $($arg)+) };
+}
+
+macro_rules! trace {
+    ($target:ident, $($arg:tt)+) => { $crate::logging::log!(Trace, $target, $($arg)+) };
+}
+
+pub(crate) use {debug, error, info, log, trace, warning};
@@ -11,9 +11,38 @@ mod pipes;
 mod number_texture_atlas;
 mod base;
 mod sound_effects;
+mod simulation;
+mod world_renderer;
+mod player_renderer;
+mod pipe_renderer;
+mod base_renderer;
+mod rng;
+mod platform;
+mod replay;
+mod storage;
+mod high_score;
+mod difficulty;
+mod pipe_placement;
+mod collision;
+mod animation;
+mod input;
+mod logging;
+mod debug_overlay;
+mod settings;
+mod settings_menu;
+mod audio_mixer;
+mod playlist;
+mod assets;
+mod loading_screen;
+mod sprite_sheet;
+mod theme;
+mod parallax;
 
 use macroquad::prelude::*;
+use crate::assets::AssetManager;
+use crate::debug_overlay::FrameStats;
 use crate::game_state::GameState;
+use crate::simulation::FIXED_DELTA;
 pub const SCREEN_WIDTH: f32 = 320.0;
 pub const SCREEN_HEIGHT: f32 = 568.0;
 
@@ -27,45 +56,47 @@ fn window_conf() -> Conf {
     }
 }
 
-fn draw_loading_screen() {
-    let center_x = screen_width() / 2.0;
-    let center_y = screen_height() / 2.0;
-
-    // Draw "Loading assets..." text
-    let text = "Loading assets...";
-    let font_size = 30.0;
-    let text_dims = measure_text(text, None, font_size as u16, 1.0);
-    let text_x = center_x - text_dims.width / 2.0;
-    let text_y = center_y;
-    draw_text(text, text_x, text_y, font_size, WHITE);
-
-    // Draw static spinner dots
-    let spinner_radius = 20.0;
-    let spinner_y = center_y + 40.0;
-
-    for i in 0..8 {
-        let angle = i as f32 * std::f32::consts::PI / 4.0;
-        let x = center_x + angle.cos() * spinner_radius;
-        let y = spinner_y + angle.sin() * spinner_radius;
-        draw_circle(x, y, 3.0, WHITE);
+const MAX_FRAME_TIME: f32 = 0.25; // Cap frame time to prevent spiral of death
+const MAX_FIXED_UPDATES_PER_FRAME: u32 = 5; // Limit updates per frame to prevent visual "wiggle" from lag spikes
+
+fn main() {
+    logging::init();
+
+    // Headless replay check: runs the simulation without opening a window
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = platform::arg("verify-replay") {
+        std::process::exit(replay::verify_file(&path));
     }
+
+    macroquad::Window::from_config(window_conf(), run());
 }
 
-const FIXED_DELTA: f32 = 1.0 / 60.0; // Fixed timestep for consistent physics
-const MAX_FRAME_TIME: f32 = 0.25; // Cap frame time to prevent spiral of death
-const MAX_FIXED_UPDATES_PER_FRAME: u32 = 5; // Limit updates per frame to prevent visual "wiggle" from lag spikes
+/// Loads everything behind the loading screen
+async fn load() -> Result<GameState, String> {
+    let mut assets = AssetManager::new(assets::ROOT);
+    assets.load(&assets::preload_files()).await?;
+    assets.add_sprite_sheet(sprite_sheet::SHEET_IMAGE, sprite_sheet::SHEET_FRAMES)?;
 
-#[macroquad::main(window_conf)]
-async fn main() {
+    // Themes still load after the bar fills, music loads in the background once the game runs
     clear_background(BLACK);
-    draw_loading_screen();
+    loading_screen::draw_loading_screen(1, 1);
     next_frame().await;
-    let message = load_texture("assets/sprites/message.png")
-        .await
-        .expect("Failed to load message");
-    message.set_filter(FilterMode::Nearest);
 
-    let mut game_state = GameState::new().await;
+    GameState::new(assets).await
+}
+
+async fn run() {
+    let mut game_state = match load().await {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            logging::error!(Game, "{}", e);
+            loop {
+                clear_background(BLACK);
+                loading_screen::draw_error_screen(&e);
+                next_frame().await;
+            }
+        }
+    };
 
     let mut accumulator = 0.0; // Tracks time to spend on fixed updates
     let mut frame_count = 0;
@@ -81,6 +112,9 @@ async fn main() {
         }
         last_delta = delta;
 
+        // Theme changes from the settings load between frames
+        game_state.update_theme().await;
+
         // Update music every frame for smooth playback
         game_state.update_music();
         
@@ -90,9 +124,10 @@ async fn main() {
         // Accumulate frame time for fixed timestep updates
         accumulator += delta;
 
-        if accumulator > MAX_FRAME_TIME {
+        let frame_time_capped = accumulator > MAX_FRAME_TIME;
+        if frame_time_capped {
             accumulator = MAX_FRAME_TIME;
-            println!("[WARNING] Frame time exceeded MAX_FRAME_TIME, capped at {:.3}s", MAX_FRAME_TIME);
+            logging::warning!(Timing, "Frame time exceeded MAX_FRAME_TIME, capped at {:.3}s", MAX_FRAME_TIME);
         }
 
         // Run fixed updates until we've consumed accumulated time (up to the cap)
@@ -104,15 +139,17 @@ async fn main() {
         }
         
         // Discard excess time if we hit the update cap (prevents catch-up spiral)
-        if fixed_updates_this_frame >= MAX_FIXED_UPDATES_PER_FRAME && accumulator >= FIXED_DELTA {
-            println!("[WARNING] Hit max fixed updates per frame ({}), discarding {:.4}s of excess time", 
+        let updates_capped = fixed_updates_this_frame >= MAX_FIXED_UPDATES_PER_FRAME && accumulator >= FIXED_DELTA;
+        if updates_capped {
+            logging::warning!(Timing, "Hit max fixed updates per frame ({}), discarding {:.4}s of excess time",
                      MAX_FIXED_UPDATES_PER_FRAME, accumulator);
             accumulator = 0.0;
         }
 
         if fixed_updates_this_frame > 1 {
-            println!(
-                "[FIXED-UPDATE] Multiple fixed updates this frame: {} (delta: {:.4}s, accumulator: {:.4}s)",
+            logging::debug!(
+                Timing,
+                "Multiple fixed updates this frame: {} (delta: {:.4}s, accumulator: {:.4}s)",
                 fixed_updates_this_frame, delta, accumulator
             );
         }
@@ -120,15 +157,23 @@ async fn main() {
         // Alpha for interpolation between fixed updates (smoother rendering)
         let alpha = accumulator / FIXED_DELTA;
 
+        game_state.record_frame(FrameStats {
+            delta,
+            fixed_updates: fixed_updates_this_frame,
+            accumulator,
+            frame_time_capped,
+            updates_capped,
+        });
+
         clear_background(BLACK);
-        game_state.draw(&message, alpha);
+        game_state.draw(alpha);
         next_frame().await;
 
         frame_count += 1;
         fps_timer += delta;
         if fps_timer >= 1.0 {
             let fps = frame_count as f32 / fps_timer;
-            println!("FPS: {:.2}", fps);
+            logging::debug!(Timing, "FPS: {:.2}", fps);
             frame_count = 0;
             fps_timer = 0.0;
         }
@@ -1,183 +1,436 @@
-use macroquad::audio::{load_sound, play_sound, set_sound_volume, stop_sound, Sound};
+use macroquad::audio::{load_sound_from_bytes, play_sound, set_sound_volume, stop_sound, Sound};
+use macroquad::experimental::coroutines::{start_coroutine, Coroutine};
 use macroquad::time::get_time;
+use crate::assets;
+use crate::audio_mixer::{AudioMixer, Bus};
+use crate::logging;
+use crate::playlist::{PlayOrder, Playlist, RepeatMode, TrackInfo, MANIFEST_FILE};
+
+/// What to do once a fade out finishes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AfterFade {
+    Next { index: usize, wrapped: bool },
+    Stop,
+    Pause,
+}
 
 enum FadeState {
     None,
-    FadingOut { next_index: Option<usize> },
+    FadingOut { then: AfterFade },
+    FadingIn,
+}
+
+/// A track's sound, they load in the background one at a time
+enum TrackSound {
+    NotLoaded,
+    Loaded(Sound),
+    Failed,
+}
+
+/// What the music is doing, see `MusicPlayer::state`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicState {
+    Stopped,
+    Playing,
     FadingIn,
+    FadingOut,
+    /// Faded out and held, `resume` fades back in
+    Paused,
+}
+
+/// The playlist, in play order, and the sound of the track that plays first
+struct FirstTrack {
+    tracks: Vec<TrackInfo>,
+    play_order: PlayOrder,
+    sound: Sound,
 }
 
 pub struct MusicPlayer {
-    sounds: Vec<Sound>,
+    folder_path: String,
+    /// Empty until the playlist has loaded, every method waits for it
+    sounds: Vec<TrackSound>,
+    starting: Option<Coroutine<Result<FirstTrack, String>>>,
+    /// Whether music should be on once the first track arrives, as left by `play`, `stop` and `next`
+    play_when_loaded: bool,
+    /// Track being loaded in the background, one at a time in play order
+    loading: Option<(usize, Coroutine<Result<Sound, String>>)>,
+    /// Next song that `next` picked before it finished loading, played once it has
+    waiting_for: Option<(usize, bool)>,
+    tracks: Vec<TrackInfo>,
+    play_order: PlayOrder,
     current_index: usize,
+    /// When the current song was last started, for `TrackInfo::loop_end`
+    song_start_time: f64,
     is_playing: bool,
     fade_state: FadeState,
     fade_start_time: f64,
     fade_duration: f64,
+    /// Fade multiplier when the current fade started, fades continue from where the last one left off
+    fade_from: f32,
+    /// Current fade multiplier, 0 to 1
+    fade_level: f32,
+    paused: bool,
+    /// Music bus volume as of the last `update`
+    bus_volume: f32,
+    /// Volume last given to the playing sound
+    applied_volume: f32,
 }
 
 impl MusicPlayer {
-    /// Creates a new MusicPlayer by loading all -compressed.ogg files from the given folder
-    pub async fn new(folder_path: &str, fade_duration: f64) -> Result<Self, String> {
-        println!("[MUSIC] Loading music from: {}", folder_path);
-
-        // Hardcoded music files for WASM compatibility
-        let music_files = vec![
-            "Hypnotic-Puzzle-compressed.ogg",
-            "Hypnotic-Puzzle2-compressed.ogg",
-            "Hypnotic-Puzzle3-compressed.ogg",
-            "Hypnotic-Puzzle4-compressed.ogg",
-        ];
-
-        println!("[MUSIC] Loading {} music files", music_files.len());
-
-        // Load all sounds sequentially
-        let mut sounds = Vec::new();
-        for filename in music_files {
-            let path = format!("{}/{}", folder_path, filename);
-            let start = get_time();
-            println!("[MUSIC] Loading: {}", filename);
-            let sound = load_sound(&path)
-                .await
-                .map_err(|e| format!("Failed to load sound {}: {}", path, e))?;
-            println!("[MUSIC] Loaded {} in {:.2}s", filename, get_time() - start);
-            sounds.push(sound);
-        }
-        Ok(Self {
-            sounds,
+    /// Creates a new MusicPlayer with the tracks listed in `playlist.json` in the given folder.
+    /// Nothing is loaded here: `update` loads the playlist and the first track to play in the background,
+    /// then the rest one by one.
+    pub fn new(folder_path: &str, fade_duration: f64) -> Self {
+        logging::info!(Audio, "Loading music from: {}", folder_path);
+
+        Self {
+            folder_path: folder_path.to_string(),
+            sounds: Vec::new(),
+            starting: Some(start_coroutine(load_playlist(folder_path.to_string()))),
+            play_when_loaded: false,
+            loading: None,
+            waiting_for: None,
+            tracks: Vec::new(),
+            play_order: PlayOrder::new(0, false, RepeatMode::Off, 0),
             current_index: 0,
+            song_start_time: 0.0,
             is_playing: false,
             fade_state: FadeState::None,
             fade_start_time: 0.0,
             fade_duration,
-        })
+            fade_from: 1.0,
+            fade_level: 1.0,
+            paused: false,
+            bus_volume: 1.0,
+            applied_volume: 1.0,
+        }
+    }
+
+    /// Picks up the playlist and first track, plays it if `play` or `next` was called before they arrived
+    fn poll_starting(&mut self) {
+        let Some(result) = self.starting.as_ref().and_then(|coroutine| coroutine.retrieve()) else {
+            return;
+        };
+        self.starting = None;
+
+        let FirstTrack { tracks, play_order, sound } = match result {
+            Ok(first_track) => first_track,
+            Err(e) => {
+                logging::warning!(Audio, "{}, playing without music", e);
+                return;
+            }
+        };
+
+        self.sounds = tracks.iter().map(|_| TrackSound::NotLoaded).collect();
+        self.current_index = play_order.current();
+        self.sounds[self.current_index] = TrackSound::Loaded(sound);
+        self.tracks = tracks;
+        self.play_order = play_order;
+        self.load_next_track();
+
+        if self.play_when_loaded {
+            self.play();
+        }
+    }
+
+    /// Starts loading the next track in play order that isn't loaded yet
+    fn load_next_track(&mut self) {
+        let Some(index) = self
+            .play_order
+            .upcoming()
+            .find(|&index| matches!(self.sounds[index], TrackSound::NotLoaded))
+        else {
+            logging::debug!(Audio, "All music loaded");
+            return;
+        };
+
+        let path = format!("{}/{}", self.folder_path, self.tracks[index].file);
+        self.loading = Some((index, start_coroutine(load_track(path))));
+    }
+
+    /// Picks up a finished background load, then starts the next one
+    fn poll_loading(&mut self) {
+        let Some((index, coroutine)) = &self.loading else {
+            return;
+        };
+        let index = *index;
+        let Some(result) = coroutine.retrieve() else {
+            return;
+        };
+
+        self.loading = None;
+        self.sounds[index] = match result {
+            Ok(sound) => TrackSound::Loaded(sound),
+            Err(e) => {
+                logging::warning!(Audio, "{}, skipping it", e);
+                TrackSound::Failed
+            }
+        };
+        self.load_next_track();
+
+        if let Some((waiting, wrapped)) = self.waiting_for
+            && waiting == index
+        {
+            self.waiting_for = None;
+            match self.sounds[index] {
+                TrackSound::Loaded(_) if self.is_playing => self.start_fade_out(AfterFade::Next { index, wrapped }),
+                TrackSound::Loaded(_) => self.start_next_song(index, wrapped),
+                _ => self.next(),
+            }
+        }
+    }
+
+    fn sound(&self) -> &Sound {
+        match &self.sounds[self.current_index] {
+            TrackSound::Loaded(sound) => sound,
+            // Songs are only switched to once loaded
+            _ => unreachable!("current track isn't loaded"),
+        }
     }
 
-    /// Start playing the current song (loops indefinitely)
+    /// Start playing the current song (loops indefinitely), or as soon as it has loaded
     pub fn play(&mut self) {
+        self.play_when_loaded = self.sounds.is_empty();
         if self.sounds.is_empty() || self.is_playing {
             return;
         }
 
-        play_sound(
-            &self.sounds[self.current_index],
-            macroquad::audio::PlaySoundParams {
-                looped: true,
-                volume: 1.0,
-            },
-        );
-
+        self.fade_level = 1.0;
+        self.start_sound(self.target_volume());
         self.is_playing = true;
+        self.paused = false;
         self.fade_state = FadeState::None;
     }
 
     /// Stop playback with fade out
     pub fn stop(&mut self) {
+        self.play_when_loaded = false;
         if self.sounds.is_empty() || !self.is_playing {
             return;
         }
 
+        self.start_fade_out(AfterFade::Stop);
+    }
+
+    /// Fades out and holds the song. macroquad can't pause a sound,
+    /// so it keeps playing silently and `resume` fades it back in where it is by then.
+    pub fn pause(&mut self) {
+        if self.sounds.is_empty() || !self.is_playing || self.paused {
+            return;
+        }
+        if let FadeState::FadingOut { .. } = self.fade_state {
+            return;
+        }
 
-        self.fade_start_time = get_time();
+        self.start_fade_out(AfterFade::Pause);
+    }
+
+    pub fn resume(&mut self) {
+        let pausing = matches!(self.fade_state, FadeState::FadingOut { then: AfterFade::Pause });
+        if !self.paused && !pausing {
+            return;
+        }
+
+        self.paused = false;
+        self.start_fade(FadeState::FadingIn);
+    }
+
+    pub fn state(&self) -> MusicState {
+        match self.fade_state {
+            _ if !self.is_playing => MusicState::Stopped,
+            FadeState::FadingOut { .. } => MusicState::FadingOut,
+            FadeState::FadingIn => MusicState::FadingIn,
+            FadeState::None if self.paused => MusicState::Paused,
+            FadeState::None => MusicState::Playing,
+        }
     }
 
     /// Skip to the next song with crossfade
     pub fn next(&mut self) {
         if self.sounds.is_empty() {
+            // Nothing played yet, so the first track is the next one
+            self.play_when_loaded = true;
             return;
         }
 
-        let next_idx = (self.current_index + 1) % self.sounds.len();
+        let Some((index, wrapped)) = self.play_order.advance() else {
+            // Repeat is off and the last track played
+            self.stop();
+            return;
+        };
+
+        match self.sounds[index] {
+            TrackSound::Loaded(_) => self.waiting_for = None,
+            TrackSound::NotLoaded => {
+                // Keep the current song until it arrives
+                logging::debug!(Audio, "{} is still loading", self.tracks[index].title);
+                self.waiting_for = Some((index, wrapped));
+                return;
+            }
+            // The current track is always loaded, so this ends there at the latest
+            TrackSound::Failed => return self.next(),
+        }
 
         if self.is_playing {
             // Start fade out to next song
-            self.fade_state = FadeState::FadingOut {
-                next_index: Some(next_idx),
-            };
-            self.fade_start_time = get_time();
+            self.start_fade_out(AfterFade::Next { index, wrapped });
         } else {
             // If not playing, just start the next song
-            self.start_next_song(next_idx);
+            self.start_next_song(index, wrapped);
         }
     }
 
-    fn start_next_song(&mut self, new_song_idx: usize) {
-        stop_sound(&self.sounds[self.current_index]);
-        let previous_current_index = self.current_index; // Store the index of the song that just ended
-        self.current_index = new_song_idx; // Update to the new song's index
+    fn start_fade_out(&mut self, then: AfterFade) {
+        self.start_fade(FadeState::FadingOut { then });
+    }
+
+    fn start_fade(&mut self, fade_state: FadeState) {
+        self.fade_state = fade_state;
+        self.fade_from = self.fade_level;
+        self.fade_start_time = get_time();
+    }
 
-        let is_wrap_around_to_first = new_song_idx == 0 && previous_current_index == self.sounds.len() - 1;
+    /// `wrapped` when the playlist started over
+    fn start_next_song(&mut self, new_song_idx: usize, wrapped: bool) {
+        stop_sound(self.sound());
+        self.current_index = new_song_idx; // Update to the new song's index
+        self.is_playing = true;
+        self.paused = false;
 
-        if is_wrap_around_to_first {
+        if wrapped {
             // "Go back to fast" - play immediately at full volume, no fade-in
-            play_sound(
-                &self.sounds[self.current_index],
-                macroquad::audio::PlaySoundParams {
-                    looped: true,
-                    volume: 1.0,
-                },
-            );
+            self.fade_level = 1.0;
+            self.start_sound(self.target_volume());
             self.fade_state = FadeState::None; // No fade in needed
         } else {
             // Normal fade-in
-            play_sound(
-                &self.sounds[self.current_index],
-                macroquad::audio::PlaySoundParams {
-                    looped: true,
-                    volume: 0.0, // Start from 0 for fade-in
-                },
-            );
-            self.fade_state = FadeState::FadingIn;
-            self.fade_start_time = get_time();
+            self.fade_level = 0.0;
+            self.start_sound(0.0); // Start from 0 for fade-in
+            self.start_fade(FadeState::FadingIn);
+        }
+    }
+
+    fn start_sound(&mut self, volume: f32) {
+        let track = &self.tracks[self.current_index];
+        match track.bpm {
+            Some(bpm) => logging::info!(Audio, "Now playing: {} ({} BPM)", track.title, bpm),
+            None => logging::info!(Audio, "Now playing: {}", track.title),
         }
+
+        play_sound(
+            self.sound(),
+            macroquad::audio::PlaySoundParams {
+                looped: true,
+                volume,
+            },
+        );
+        self.applied_volume = volume;
+        self.song_start_time = get_time();
+    }
+
+    /// Full volume of the current song: the music bus times the track's own volume
+    fn target_volume(&self) -> f32 {
+        self.bus_volume * self.tracks[self.current_index].volume
     }
 
-    /// Call this every frame in your game loop to handle fades
-    pub fn update(&mut self) {
+    /// Call this every frame in your game loop to handle fades.
+    /// Fades are multiplied by the music bus, so volume changes and mute apply even mid-fade.
+    pub fn update(&mut self, mixer: &AudioMixer) {
+        self.bus_volume = mixer.volume(Bus::Music);
+        self.poll_starting();
+        self.poll_loading();
         if self.sounds.is_empty() || !self.is_playing {
             return;
         }
 
         // Handle fading
-        match &self.fade_state {
-            FadeState::FadingOut { next_index } => {
-                let fade_progress = (get_time() - self.fade_start_time) / self.fade_duration;
+        let fade_progress = (((get_time() - self.fade_start_time) / self.fade_duration) as f32).min(1.0);
+        match self.fade_state {
+            FadeState::FadingOut { then } => {
+                // Gradually decrease volume
+                self.fade_level = self.fade_from * (1.0 - fade_progress);
                 if fade_progress >= 1.0 {
                     // Fade out complete
-                    println!("[MUSIC] Fade out complete, next_index={:?}", next_index);
-                    if let Some(next_idx) = *next_index {
-                        // Start next song
-                        println!("[MUSIC] Fade out complete, starting next song {}", next_idx);
-                        self.start_next_song(next_idx);
-                    } else {
-                        // Just stop
-                        println!("[MUSIC] next_index is None, stopping playback");
-                        stop_sound(&self.sounds[self.current_index]);
-                        self.is_playing = false;
-                        self.fade_state = FadeState::None;
+                    logging::debug!(Audio, "Fade out complete, then {:?}", then);
+                    self.fade_state = FadeState::None;
+                    match then {
+                        AfterFade::Next { index, wrapped } => {
+                            // Start next song
+                            logging::debug!(Audio, "Fade out complete, starting next song {}", index);
+                            self.start_next_song(index, wrapped);
+                            return;
+                        }
+                        AfterFade::Stop => {
+                            // Just stop
+                            logging::debug!(Audio, "Stopping playback");
+                            stop_sound(self.sound());
+                            self.is_playing = false;
+                            return;
+                        }
+                        AfterFade::Pause => self.paused = true,
                     }
-                } else {
-                    // Gradually decrease volume
-                    let volume = 1.0 - fade_progress;
-                    set_sound_volume(&self.sounds[self.current_index], volume as f32);
                 }
             }
             FadeState::FadingIn => {
-                let fade_progress = (get_time() - self.fade_start_time) / self.fade_duration;
+                // Gradually increase volume
+                self.fade_level = self.fade_from + (1.0 - self.fade_from) * fade_progress;
                 if fade_progress >= 1.0 {
                     // Fade in complete
-                    set_sound_volume(&self.sounds[self.current_index], 1.0);
                     self.fade_state = FadeState::None;
-                } else {
-                    // Gradually increase volume
-                    let volume = fade_progress;
-                    set_sound_volume(&self.sounds[self.current_index], volume as f32);
                 }
             }
             FadeState::None => {
                 // Just playing normally, looping
             }
         }
+
+        // Sounds can only loop whole, so an earlier loop end restarts the song by hand
+        if let Some(loop_end) = self.tracks[self.current_index].loop_end
+            && get_time() - self.song_start_time >= loop_end as f64
+        {
+            stop_sound(self.sound());
+            let volume = self.applied_volume;
+            play_sound(
+                self.sound(),
+                macroquad::audio::PlaySoundParams { looped: true, volume },
+            );
+            self.song_start_time = get_time();
+        }
+
+        self.set_volume(self.fade_level * self.target_volume());
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        if (volume - self.applied_volume).abs() > f32::EPSILON {
+            set_sound_volume(self.sound(), volume);
+            self.applied_volume = volume;
+        }
     }
+}
+
+async fn load_playlist(folder_path: String) -> Result<FirstTrack, String> {
+    let manifest_path = format!("{}/{}", folder_path, MANIFEST_FILE);
+    let manifest = assets::load_bytes(&manifest_path)
+        .await
+        .and_then(|bytes| String::from_utf8(bytes).map_err(|e| format!("{}: {}", manifest_path, e)))
+        .map_err(|e| format!("Failed to load playlist: {}", e))?;
+    let playlist = Playlist::parse(&manifest).map_err(|e| format!("{}: {}", manifest_path, e))?;
+
+    logging::debug!(Audio, "Playlist has {} music files", playlist.tracks.len());
+
+    let seed = macroquad::miniquad::date::now().to_bits();
+    let play_order = PlayOrder::new(playlist.tracks.len(), playlist.shuffle, playlist.repeat, seed);
+    let sound = load_track(format!("{}/{}", folder_path, playlist.tracks[play_order.current()].file)).await?;
+    Ok(FirstTrack { tracks: playlist.tracks, play_order, sound })
+}
 
+async fn load_track(path: String) -> Result<Sound, String> {
+    let start = get_time();
+    logging::debug!(Audio, "Loading: {}", path);
+    let bytes = assets::load_bytes(&path).await?;
+    let sound = load_sound_from_bytes(&bytes)
+        .await
+        .map_err(|e| format!("Failed to decode sound {}: {}", path, e))?;
+    logging::info!(Audio, "Loaded {} in {:.2}s", path, get_time() - start);
+    Ok(sound)
 }
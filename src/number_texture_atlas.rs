@@ -1,7 +1,7 @@
-use macroquad::texture::{load_texture, FilterMode, Texture2D};
+use crate::sprite_sheet::Sprite;
 
 pub struct NumberSprites {
-    pub digits: [Texture2D; 10],
+    pub digits: [Sprite; 10],
 }
 
 pub struct NumberTextureAtlas {
@@ -11,34 +11,14 @@ pub struct NumberTextureAtlas {
 }
 
 impl NumberTextureAtlas {
-    pub async fn new() -> NumberTextureAtlas {
-        let mut digits = Vec::new();
-
-        for i in 0..10 {
-            let texture = Self::get_texture(i).await;
-            texture.set_filter(FilterMode::Nearest);
-            digits.push(texture);
-        }
-
+    pub fn new(digits: [Sprite; 10]) -> NumberTextureAtlas {
         let w = digits[0].width();
         let h = digits[0].height();
 
-        // Convert Vec to array
-        let digits_array: [Texture2D; 10] = digits.try_into().unwrap();
-
         NumberTextureAtlas {
-            number_sprites: NumberSprites {
-                digits: digits_array,
-            },
+            number_sprites: NumberSprites { digits },
             width: w,
             height: h,
         }
     }
-
-    pub async fn get_texture(digit: usize) -> Texture2D {
-        let path = format!("assets/sprites/{}.png", digit);
-        load_texture(&path)
-            .await
-            .expect("Failed to load texture")
-    }
-}
\ No newline at end of file
+}
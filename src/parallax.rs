@@ -0,0 +1,95 @@
+use crate::components::Node;
+
+/// Scroll of one background layer. `offset` wraps around at the layer's tile width to keep its precision,
+/// where the wrap doesn't show whatever the speed.
+struct LayerScroll {
+    speed: f32,
+    width: f32,
+    offset: f32,
+    previous_offset: f32,
+}
+
+/// Moves the background layers by a fraction of the world's velocity
+pub struct Parallax {
+    pub velocity: u16,
+    layers: Vec<LayerScroll>,
+    pub stopped: bool,
+}
+
+impl Parallax {
+    pub fn new(velocity: u16) -> Parallax {
+        Parallax {
+            velocity,
+            layers: Vec::new(),
+            stopped: false,
+        }
+    }
+
+    /// Replaces the layers by `(speed, tile width)` pairs, back to front, scrolled from the start
+    pub fn set_layers(&mut self, layers: impl IntoIterator<Item = (f32, f32)>) {
+        self.layers = layers
+            .into_iter()
+            .map(|(speed, width)| LayerScroll { speed, width, offset: 0.0, previous_offset: 0.0 })
+            .collect();
+    }
+
+    pub fn stop(&mut self) {
+        self.stopped = true;
+        self.layers.iter_mut().for_each(|layer| layer.previous_offset = layer.offset);
+    }
+
+    /// Carries on from where it stopped, so the background doesn't jump between runs
+    pub fn restart(&mut self) {
+        self.stopped = false;
+        self.layers.iter_mut().for_each(|layer| layer.previous_offset = layer.offset);
+    }
+
+    /// How far `layer` has scrolled within its tile, between the last two fixed updates
+    pub fn offset(&self, layer: usize, alpha: f32) -> f32 {
+        self.layers.get(layer).map_or(0.0, |layer| {
+            let offset = layer.previous_offset + (layer.offset - layer.previous_offset) * alpha;
+            offset.rem_euclid(layer.width)
+        })
+    }
+}
+
+impl Node for Parallax {
+    fn update(&mut self, dt: f32) {
+        for layer in &mut self.layers {
+            layer.previous_offset = layer.offset;
+
+            if !self.stopped {
+                layer.offset += self.velocity as f32 * dt * layer.speed;
+
+                // The previous offset moves along so the wrap doesn't show when interpolating
+                if layer.offset >= layer.width {
+                    let wrapped = layer.offset % layer.width;
+                    layer.previous_offset -= layer.offset - wrapped;
+                    layer.offset = wrapped;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_wrap_at_their_own_width() {
+        let mut parallax = Parallax::new(100);
+        parallax.set_layers([(0.3, 10.0), (1.0, 288.0)]);
+
+        let mut previous = 0.0;
+        for _ in 0..1000 {
+            parallax.update(0.1);
+            let offset = parallax.offset(0, 0.5);
+            // Moves 3 per update, so half an update later is 1.5 on, modulo the tile
+            assert!(((offset - previous).rem_euclid(10.0) - 1.5).abs() < 1e-3);
+            previous = parallax.offset(0, 1.0);
+            assert!((0.0..10.0).contains(&previous));
+        }
+        assert!((parallax.offset(1, 1.0) - 10_000.0 % 288.0).abs() < 1e-2);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::components::Size;
+use crate::difficulty::DifficultyLevel;
+use crate::pipes::PipeLocation;
+use crate::player::MAX_FALL_SPEED;
+use crate::rng::Rng;
+use crate::simulation::FIXED_DELTA;
+
+/// Picks the next pipe gap so it can always be reached from the previous one,
+/// based on how far the bird can climb or drop while flying between the two pairs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipePlacer {
+    pub jump_force: f32,
+    pub gravity: f32,
+    pub bird: Size,
+    pub pipe_width: f32,
+}
+
+impl PipePlacer {
+    pub fn new(jump_force: f32, gravity: f32, bird: Size, pipe_width: f32) -> Self {
+        PipePlacer { jump_force, gravity, bird, pipe_width }
+    }
+
+    /// Seconds between the bird leaving the previous pair and entering the next one
+    fn travel_time(&self, level: &DifficultyLevel) -> f32 {
+        let speed = level.speed as f32;
+        let distance = speed * level.spawn_interval - self.pipe_width - self.bird.width;
+        (distance / speed).max(0.0)
+    }
+
+    /// How far the bird can climb and drop in `time`, stepping the same physics as `Player`.
+    /// The climb assumes a flap only once the bird stops rising, which any player can manage.
+    fn reach(&self, time: f32) -> (f32, f32) {
+        let steps = (time / FIXED_DELTA) as u32;
+
+        let mut y = 0.0f32;
+        let mut vel = 0.0f32;
+        let mut max_rise = 0.0f32;
+        for _ in 0..steps {
+            if vel >= 0.0 {
+                vel = self.jump_force;
+            }
+            vel = (vel + self.gravity * FIXED_DELTA).min(MAX_FALL_SPEED);
+            y += vel * FIXED_DELTA;
+            max_rise = max_rise.max(-y);
+        }
+
+        let mut max_drop = 0.0f32;
+        let mut vel = 0.0f32;
+        for _ in 0..steps {
+            vel = (vel + self.gravity * FIXED_DELTA).min(MAX_FALL_SPEED);
+            max_drop += vel * FIXED_DELTA;
+        }
+
+        (max_rise, max_drop)
+    }
+
+    /// Returns the index of the next `PipeLocation`. Every candidate must be reachable from
+    /// anywhere in the previous gap, with a bird's height to spare.
+    pub fn next_location(&self,
+                         rng: &mut Rng,
+                         last_index: usize,
+                         last_gap_size: f32,
+                         level: &DifficultyLevel,
+                         base_height: f32) -> usize {
+        let (max_rise, max_drop) = self.reach(self.travel_time(level));
+        let margin = self.bird.height;
+
+        let last_top = PipeLocation::from_index(last_index).gap_top(last_gap_size, base_height);
+        let last_bottom = last_top + last_gap_size;
+
+        let candidates: Vec<usize> = (0..PipeLocation::COUNT)
+            .filter(|index| index.abs_diff(last_index) <= level.max_location_jump)
+            .filter(|&index| {
+                let top = PipeLocation::from_index(index).gap_top(level.gap_size, base_height);
+                let bottom = top + level.gap_size;
+
+                // Climb from the bottom of the last gap, or drop from its top
+                let rise = last_bottom - bottom;
+                let drop = top - last_top;
+                rise + margin <= max_rise && drop + margin <= max_drop
+            })
+            .collect();
+
+        if candidates.is_empty() {
+            // Staying put is always reachable
+            return last_index;
+        }
+        candidates[rng.gen_range(0, candidates.len())]
+    }
+}
@@ -0,0 +1,34 @@
+use macroquad::math::vec2;
+use macroquad::prelude::DrawTextureParams;
+use crate::components::Renderer;
+use crate::pipe_texture_atlas::PipeTextureAtlas;
+use crate::pipes::Pipe;
+
+pub struct PipeRenderer {
+    pub pipe_texture_atlas: PipeTextureAtlas,
+}
+
+impl PipeRenderer {
+    pub fn new(pipe_texture_atlas: PipeTextureAtlas) -> Self {
+        PipeRenderer { pipe_texture_atlas }
+    }
+}
+
+impl Renderer<Pipe> for PipeRenderer {
+    fn draw(&mut self, pipe: &Pipe, alpha: f32) {
+        let (y, pipe_height) = pipe.get_pipe_y_and_height();
+        let pipe_width = pipe.size.width;
+
+        let texture = self.pipe_texture_atlas.get_texture_2d(pipe.score);
+
+        texture.draw(
+            pipe.interpolated_x(alpha),
+            y,
+            DrawTextureParams {
+                dest_size: Some(vec2(pipe_width, pipe_height)),
+                flip_y: pipe.reflected,
+                ..Default::default()
+            },
+        );
+    }
+}
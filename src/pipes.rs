@@ -1,10 +1,7 @@
-use std::rc::Rc;
-use macroquad::color::WHITE;
-use macroquad::math::{vec2, Vec2};
-use macroquad::prelude::{draw_texture_ex, DrawTextureParams};
+use macroquad::math::{vec2, Rect, Vec2};
 use crate::{SCREEN_WIDTH, SCREEN_HEIGHT};
-use crate::components::Node;
-use crate::pipe_texture_atlas::{PipeColor, PipeTextureAtlas};
+use crate::collision::{PipeShape, Shape};
+use crate::components::{Node, Size};
 use crate::player::Player;
 
 #[derive(Clone, Debug)]
@@ -21,27 +18,75 @@ pub enum PipeLocation {
     Lowest,
 }
 
+impl PipeLocation {
+    pub const COUNT: usize = 10;
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            0 => PipeLocation::VeryHigh,
+            1 => PipeLocation::High,
+            2 => PipeLocation::HighMid,
+            3 => PipeLocation::UpperMid,
+            4 => PipeLocation::Mid,
+            5 => PipeLocation::LowerMid,
+            6 => PipeLocation::LowMid,
+            7 => PipeLocation::Low,
+            8 => PipeLocation::VeryLow,
+            _ => PipeLocation::Lowest,
+        }
+    }
+
+    /// Percentage within the valid bounds
+    fn percentage(&self) -> f32 {
+        match self {
+            PipeLocation::VeryHigh => 0.0,
+            PipeLocation::High => 0.111,
+            PipeLocation::HighMid => 0.222,
+            PipeLocation::UpperMid => 0.333,
+            PipeLocation::Mid => 0.444,
+            PipeLocation::LowerMid => 0.555,
+            PipeLocation::LowMid => 0.666,
+            PipeLocation::Low => 0.777,
+            PipeLocation::VeryLow => 0.888,
+            PipeLocation::Lowest => 1.0,
+        }
+    }
+
+    /// Y of the bottom of the top pipe, i.e. where the gap starts
+    pub fn gap_top(&self, gap_size: f32, base_height: f32) -> f32 {
+        let playable_height = SCREEN_HEIGHT - base_height;
+
+        // Valid range for the bottom of the top pipe
+        let min_bottom_y = 20.0;
+        let max_bottom_y = playable_height - gap_size - 20.0;
+        let range = max_bottom_y - min_bottom_y;
+
+        min_bottom_y + (range * self.percentage())
+    }
+}
+
 
 pub struct Pipe {
-    pub pipe_texture_atlas: Rc<PipeTextureAtlas>,
+    pub size: Size,
     pub velocity: u16,
     pub reflected: bool,
-    position: Vec2,
+    pub position: Vec2,
+    previous_x: f32,
     pipe_location: PipeLocation,
+    gap_size: f32,
     base_height: f32,
     stopped: bool,
-    score: u32,
+    pub score: u32,
     pub passed: bool,
 }
 
 
-const GAP_SIZE: f32 = 140.0;
-
 impl Pipe {
-    pub fn new(pipe_texture_atlas: Rc<PipeTextureAtlas>,
+    pub fn new(size: Size,
                reflected: bool,
                velocity: u16,
                pipe_location: PipeLocation,
+               gap_size: f32,
                base_height: f32,
                score: u32,) -> Pipe {
 
@@ -50,11 +95,13 @@ impl Pipe {
         let y = 0.0;
 
         Pipe {
-            pipe_texture_atlas,
+            size,
             velocity,
             reflected,
             position: vec2(x, y),
+            previous_x: x,
             pipe_location,
+            gap_size,
             base_height,
             stopped: false,
             score,
@@ -68,6 +115,12 @@ impl Pipe {
 
     pub fn stop(&mut self) {
         self.stopped = true;
+        self.previous_x = self.position.x;
+    }
+
+    /// X to draw at, between the last two fixed updates
+    pub fn interpolated_x(&self, alpha: f32) -> f32 {
+        self.previous_x + (self.position.x - self.previous_x) * alpha
     }
 
     pub fn restart(&mut self) {
@@ -75,35 +128,13 @@ impl Pipe {
     }
 
     pub fn is_off_screen(&self) -> bool {
-        self.position.x + self.pipe_texture_atlas.width < 0.0
+        self.position.x + self.size.width < 0.0
     }
 
-    fn get_pipe_y_and_height(&self) -> (f32, f32) {
-        let screen_h = SCREEN_HEIGHT;
-        let playable_height = screen_h - self.base_height;
-        let gap_size = GAP_SIZE;
-
-        // Valid range for the bottom of the top pipe
-        let min_bottom_y = 20.0;
-        let max_bottom_y = playable_height - gap_size - 20.0;
-        let range = max_bottom_y - min_bottom_y;
-
-        // Percentage within the valid bounds
-        let percentage = match self.pipe_location {
-            PipeLocation::VeryHigh => 0.0,
-            PipeLocation::High => 0.111,
-            PipeLocation::HighMid => 0.222,
-            PipeLocation::UpperMid => 0.333,
-            PipeLocation::Mid => 0.444,
-            PipeLocation::LowerMid => 0.555,
-            PipeLocation::LowMid => 0.666,
-            PipeLocation::Low => 0.777,
-            PipeLocation::VeryLow => 0.888,
-            PipeLocation::Lowest => 1.0,
-        };
-
-        let top_pipe_bottom_y = min_bottom_y + (range * percentage);
-        let pipe_height = self.pipe_texture_atlas.height;
+    pub fn get_pipe_y_and_height(&self) -> (f32, f32) {
+        let gap_size = self.gap_size;
+        let top_pipe_bottom_y = self.pipe_location.gap_top(gap_size, self.base_height);
+        let pipe_height = self.size.height;
 
         if self.reflected {
             // Top pipe (upside down) - bottom edge at top_pipe_bottom_y
@@ -116,52 +147,30 @@ impl Pipe {
         }
     }
 
-    pub fn touched(&self, player: &Player) -> bool {
+    /// Sprite rectangle the hitbox covers
+    pub fn hit_rect(&self) -> Rect {
         let (pipe_y, pipe_height) = self.get_pipe_y_and_height();
-        let pipe_x = self.position.x;
-        let pipe_width = self.pipe_texture_atlas.width;
-
-        let player_x = player.position.x;
-        let player_y = player.position.y;
-        let player_width = player.bird_renderer.width;
-        let player_height = player.bird_renderer.height;
-
-        // AABB collision detection
-        pipe_x < player_x + player_width
-            && pipe_x + pipe_width > player_x
-            && pipe_y < player_y + player_height
-            && pipe_y + pipe_height > player_y
+        Rect::new(self.position.x, pipe_y, self.size.width, pipe_height)
+    }
+
+    pub fn touched(&self, bird: &Shape, shape: &PipeShape) -> bool {
+        let rect = self.hit_rect();
+
+        match shape {
+            PipeShape::Rect => bird.overlaps_rect(&rect),
+            // Top pipes are drawn flipped, so is their mask
+            PipeShape::Mask(mask) => bird.overlaps_mask(mask, &rect, self.reflected),
+        }
     }
 }
 
 impl Node for Pipe {
     fn update(&mut self, dt: f32) {
+        self.previous_x = self.position.x;
+
         // Only move if not stopped
         if !self.stopped {
             self.position.x -= self.velocity as f32 * dt;
         }
     }
-
-    fn draw(&mut self) {
-        let (y, pipe_height) = self.get_pipe_y_and_height();
-        let pipe_width = self.pipe_texture_atlas.width;
-
-        let texture = if (self.score / 5) % 2 != 0 {
-            self.pipe_texture_atlas.get_texture_2d(PipeColor::Green)
-        } else {
-            self.pipe_texture_atlas.get_texture_2d(PipeColor::Red)
-        };
-
-        draw_texture_ex(
-            &texture,
-            self.position.x,
-            y,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(vec2(pipe_width, pipe_height)),
-                flip_y: self.reflected,
-                ..Default::default()
-            },
-        );
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,135 @@
+//! Launch options and browser access that differ between native and wasm builds.
+//! Native builds read `--name value` command line flags, the web build reads the URL query
+//! through `frontend/static/flappy_plugin.js`.
+
+/// Returns the value of a launch option, e.g. `--seed 42` or `?seed=42`.
+/// Flags given without a value return an empty string.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn arg(name: &str) -> Option<String> {
+    let flag = format!("--{}", name);
+    let prefix = format!("--{}=", name);
+    let mut args = std::env::args().skip(1).peekable();
+
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            let value = args.next_if(|next| !next.starts_with("--"));
+            return Some(value.unwrap_or_default());
+        }
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn arg(name: &str) -> Option<String> {
+    read_js_string(|out, cap| unsafe {
+        web::flappy_query_param(name.as_ptr(), name.len() as u32, out, cap)
+    })
+}
+
+/// Buttons held on the first gamepad as a bitmask of the browser's standard layout indices.
+/// macroquad has no gamepad support, so native builds report none.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn gamepad_buttons() -> u32 {
+    0
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn gamepad_buttons() -> u32 {
+    unsafe { web::flappy_gamepad_buttons() }
+}
+
+/// Notices when the window is minimized or, on the web, the tab is hidden or loses focus
+pub struct FocusWatcher {
+    subscriber: usize,
+    lost: bool,
+}
+
+impl FocusWatcher {
+    pub fn new() -> Self {
+        FocusWatcher {
+            subscriber: macroquad::input::utils::register_input_subscriber(),
+            lost: false,
+        }
+    }
+
+    /// True when focus was lost since the last call, even if it came back in the same frame
+    pub fn lost_focus(&mut self) -> bool {
+        self.lost = false;
+        macroquad::input::utils::repeat_all_miniquad_input(self, self.subscriber);
+        self.lost
+    }
+}
+
+impl macroquad::miniquad::EventHandler for FocusWatcher {
+    fn update(&mut self) {}
+
+    fn draw(&mut self) {}
+
+    fn window_minimized_event(&mut self) {
+        self.lost = true;
+    }
+}
+
+/// Writes to the browser console, `level` is a `logging::Level` discriminant
+#[cfg(target_arch = "wasm32")]
+pub fn console_log(level: u32, message: &str) {
+    unsafe { web::flappy_console_log(level, message.as_ptr(), message.len() as u32) }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn local_storage_get(key: &str) -> Option<String> {
+    read_js_string(|out, cap| unsafe {
+        web::flappy_storage_get(key.as_ptr(), key.len() as u32, out, cap)
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn local_storage_set(key: &str, value: &str) -> Result<(), String> {
+    let stored = unsafe {
+        web::flappy_storage_set(key.as_ptr(), key.len() as u32, value.as_ptr(), value.len() as u32)
+    };
+    if stored {
+        Ok(())
+    } else {
+        Err(format!("localStorage refused {}", key))
+    }
+}
+
+/// Calls a plugin function that copies a string into our buffer and returns its length
+/// (-1 when there is no value). Grows the buffer and retries when it was too small.
+#[cfg(target_arch = "wasm32")]
+fn read_js_string(read: impl Fn(*mut u8, u32) -> i32) -> Option<String> {
+    let mut buffer = vec![0u8; 256];
+    loop {
+        let len = read(buffer.as_mut_ptr(), buffer.len() as u32);
+        if len < 0 {
+            return None;
+        }
+        let len = len as usize;
+        if len <= buffer.len() {
+            buffer.truncate(len);
+            return String::from_utf8(buffer).ok();
+        }
+        buffer.resize(len, 0);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    unsafe extern "C" {
+        pub fn flappy_query_param(name: *const u8, name_len: u32, out: *mut u8, out_cap: u32) -> i32;
+        pub fn flappy_storage_get(key: *const u8, key_len: u32, out: *mut u8, out_cap: u32) -> i32;
+        pub fn flappy_storage_set(key: *const u8, key_len: u32, value: *const u8, value_len: u32) -> bool;
+        pub fn flappy_gamepad_buttons() -> u32;
+        pub fn flappy_console_log(level: u32, message: *const u8, message_len: u32);
+    }
+
+    /// Checked by mq_js_bundle.js against the `version` of flappy_plugin.js
+    #[unsafe(no_mangle)]
+    pub extern "C" fn flappy_crate_version() -> u32 {
+        3
+    }
+}
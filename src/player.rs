@@ -1,36 +1,73 @@
-use macroquad::color::WHITE;
 use macroquad::math::{vec2, Vec2};
-use macroquad::prelude::draw_texture_ex;
-use macroquad::texture::DrawTextureParams;
 use crate::{SCREEN_WIDTH, SCREEN_HEIGHT};
-use crate::bird_texture_atlas::BirdTextureAtlas;
-use crate::components::Node;
+use crate::animation::SpriteAnimation;
+use crate::collision::{BirdShape, Shape};
+use crate::components::{Node, Size};
+use crate::logging;
+
+pub const GRAVITY: f32 = 800.0;
+pub const MAX_FALL_SPEED: f32 = 1500.0;
+pub const MAX_HORIZONTAL_SPEED: f32 = 300.0;
+
+// Tilt, in radians (positive turns the nose down)
+const NOSE_UP_ROTATION: f32 = -0.45;
+const DIVE_ROTATION: f32 = std::f32::consts::FRAC_PI_2;
+/// Falling speed at which the bird points straight down
+const DIVE_VELOCITY: f32 = 600.0;
+/// How fast rotation catches up with its target, per second
+const TILT_UP_RATE: f32 = 20.0;
+const TILT_DOWN_RATE: f32 = 6.0;
+/// Spin of the dead bird, radians per second
+const TUMBLE_SPEED: f32 = -8.0;
+
+/// Wing frames (see `BirdSprites::frame`): down, mid, up, mid
+const WING_SEQUENCE: [usize; 4] = [0, 1, 2, 1];
+const WING_FRAMES_PER_SECOND: f32 = 10.0;
+/// Wings beat faster for a moment after each flap
+const FLAP_BOOST_MULTIPLIER: f32 = 3.0;
+const FLAP_BOOST_DURATION: f32 = 0.25;
+
+// Gentle bob on the start screen
+const HOVER_HEIGHT: f32 = 5.0;
+const HOVER_SPEED: f32 = 4.0;
 
 pub struct Player {
     pub alive: bool,
     pub position: Vec2,
+    pub previous_position: Vec2,
     pub jump_force: f32,
     pub vel: Vec2,
-    pub bird_renderer: BirdTextureAtlas,
+    pub size: Size,
+    pub shape: BirdShape,
     pub start_position: Vec2,
     pub rotation: f32,
+    pub previous_rotation: f32,
+    pub wing_animation: SpriteAnimation,
+    /// Only set while waiting on the start screen, drives `hover_offset`
+    pub hovering: bool,
+    hover_time: f32,
 }
 
 impl Player {
-    pub(crate) async fn new() -> Self {
-        let bird_renderer = BirdTextureAtlas::new().await;
-        let x = SCREEN_WIDTH / 2.0 - bird_renderer.width / 2.0;
-        let y = SCREEN_HEIGHT / 2.0 - bird_renderer.height / 2.0;
+    pub fn new(size: Size, shape: BirdShape) -> Self {
+        let x = SCREEN_WIDTH / 2.0 - size.width / 2.0;
+        let y = SCREEN_HEIGHT / 2.0 - size.height / 2.0;
 
 
         Self {
             alive: true,
             position: Vec2::new(x, y),
+            previous_position: Vec2::new(x, y),
             vel: Vec2::new(0.0, 0.0),
             jump_force: -300.0,
-            bird_renderer,
+            size,
+            shape,
             start_position: Vec2::new(x, y),
             rotation: 0.0,
+            previous_rotation: 0.0,
+            wing_animation: SpriteAnimation::new(&WING_SEQUENCE, WING_FRAMES_PER_SECOND),
+            hovering: false,
+            hover_time: 0.0,
         }
     }
 
@@ -40,13 +77,16 @@ impl Player {
             self.vel.y = self.jump_force;
         }
         self.alive = false;
+        self.wing_animation.freeze();
     }
 
     pub fn restart(&mut self) {
         self.alive = true;
         self.position = self.start_position;
+        self.previous_position = self.start_position;
         self.vel = Vec2::new(0.0, 0.0);
         self.rotation = 0.0;
+        self.previous_rotation = 0.0;
     }
 
     /// Handle jump input (called with buffered input state)
@@ -57,54 +97,75 @@ impl Player {
             } else {
                 self.jump_force
             };
-            println!("{}", self.vel.y);
+            logging::trace!(Physics, "Flap, vertical speed {}", self.vel.y);
+            self.wing_animation.boost(FLAP_BOOST_MULTIPLIER, FLAP_BOOST_DURATION);
+        }
+    }
+
+    /// Idles on the start screen: flaps in place and bobs without moving the hitbox
+    pub fn hover(&mut self, dt: f32) {
+        self.hovering = true;
+        self.hover_time += dt;
+        self.wing_animation.update(dt);
+    }
+
+    /// Vertical offset to draw the hovering bird at
+    pub fn hover_offset(&self) -> f32 {
+        if self.hovering {
+            (self.hover_time * HOVER_SPEED).sin() * HOVER_HEIGHT
+        } else {
+            0.0
+        }
+    }
+
+    /// Hitbox in world space, following the bird's rotation
+    pub fn hitbox(&self) -> Shape {
+        self.shape.at(self.position, vec2(self.size.width, self.size.height), self.rotation)
+    }
+
+    /// Position to draw at, between the last two fixed updates
+    pub fn interpolated_position(&self, alpha: f32) -> Vec2 {
+        self.previous_position.lerp(self.position, alpha)
+    }
+
+    /// Rotation to draw with, between the last two fixed updates
+    pub fn interpolated_rotation(&self, alpha: f32) -> f32 {
+        self.previous_rotation + (self.rotation - self.previous_rotation) * alpha
+    }
+
+    /// Nose up while rising, turning towards a dive the faster the bird falls
+    fn target_rotation(&self) -> f32 {
+        if self.vel.y < 0.0 {
+            NOSE_UP_ROTATION
+        } else {
+            let fall = (self.vel.y / DIVE_VELOCITY).min(1.0);
+            NOSE_UP_ROTATION + (DIVE_ROTATION - NOSE_UP_ROTATION) * fall
         }
     }
 }
 impl Node for Player {
 
     fn update(&mut self, dt: f32) {
-        let gravity = 800.0;
-        let max_fall_speed = 1500.0;
-        let max_horizontal_speed = 300.0;
-
-        self.vel.y += gravity * dt;
+        self.previous_position = self.position;
+        self.previous_rotation = self.rotation;
+        self.hovering = false;
+        self.wing_animation.update(dt);
+        self.vel.y += GRAVITY * dt;
 
         // Cap velocities to prevent overflow
-        self.vel.y = self.vel.y.clamp(-max_fall_speed, max_fall_speed);
-        self.vel.x = self.vel.x.clamp(-max_horizontal_speed, max_horizontal_speed);
+        self.vel.y = self.vel.y.clamp(-MAX_FALL_SPEED, MAX_FALL_SPEED);
+        self.vel.x = self.vel.x.clamp(-MAX_HORIZONTAL_SPEED, MAX_HORIZONTAL_SPEED);
 
         self.position.y += self.vel.y * dt;
         self.position.x += self.vel.x * dt;
 
-        if !self.alive {
-            self.rotation -= 1.5 * dt;
-        }
-    }
-
-    fn draw(&mut self) {
-        let (texture, rotation) = if self.vel.y < -50.0 {
-            (&self.bird_renderer.bird_sprites.downflap_texture, 25.0)
-        } else if self.vel.y > 50.0 {
-            (&self.bird_renderer.bird_sprites.upflap_texture, -25.0)
+        if self.alive {
+            let target = self.target_rotation();
+            let rate = if target < self.rotation { TILT_UP_RATE } else { TILT_DOWN_RATE };
+            self.rotation += (target - self.rotation) * (rate * dt).min(1.0);
         } else {
-            (&self.bird_renderer.bird_sprites.midflap_texture, 0.0)
-        };
-        self.rotation = rotation;
-
-        let width = texture.width();
-        let height = texture.height();
-
-        draw_texture_ex(
-            texture,
-            self.position.x,
-            self.position.y,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(vec2(width, height)),
-                rotation: self.rotation,
-                ..Default::default()
-            },
-        );
+            // The dead bird tumbles as it falls
+            self.rotation += TUMBLE_SPEED * dt;
+        }
     }
-}
\ No newline at end of file
+}
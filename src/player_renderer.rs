@@ -0,0 +1,60 @@
+use macroquad::math::vec2;
+use macroquad::texture::DrawTextureParams;
+use crate::bird_texture_atlas::BirdTextureAtlas;
+use crate::components::Renderer;
+use crate::player::Player;
+
+pub struct PlayerRenderer {
+    /// One atlas per bird of the theme, so the bird can change without loading
+    bird_renderers: Vec<(String, BirdTextureAtlas)>,
+    /// Index into the theme's birds
+    pub bird: usize,
+    /// Draws the bird level and without the start screen bob
+    pub reduced_motion: bool,
+}
+
+impl PlayerRenderer {
+    pub fn new(bird_renderers: Vec<(String, BirdTextureAtlas)>) -> Self {
+        PlayerRenderer { bird_renderers, bird: 0, reduced_motion: false }
+    }
+
+    /// Bird names in the order `bird` indexes them
+    pub fn bird_names(&self) -> Vec<String> {
+        self.bird_renderers.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    fn bird_renderer(&self) -> &BirdTextureAtlas {
+        let index = self.bird.min(self.bird_renderers.len() - 1);
+        &self.bird_renderers[index].1
+    }
+}
+
+impl Renderer<Player> for PlayerRenderer {
+    fn draw(&mut self, player: &Player, alpha: f32) {
+        let mut position = player.interpolated_position(alpha);
+        if !self.reduced_motion {
+            position.y += player.hover_offset();
+        }
+        let texture = self
+            .bird_renderer()
+            .bird_sprites
+            .frame(player.wing_animation.current_frame());
+        let rotation = if self.reduced_motion { 0.0 } else { player.interpolated_rotation(alpha) };
+
+        // Fit the hitbox, whatever size the theme's sprite is
+        let width = player.size.width;
+        let height = player.size.height;
+        let center = vec2(position.x + width / 2.0, position.y + height / 2.0);
+
+        texture.draw(
+            position.x,
+            position.y,
+            DrawTextureParams {
+                dest_size: Some(vec2(width, height)),
+                rotation,
+                pivot: Some(center),
+                ..Default::default()
+            },
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Music playlist read from `playlist.json` in the music folder.
+//!
+//! ```json
+//! {
+//!     "shuffle": false,
+//!     "repeat": "all",
+//!     "tracks": [
+//!         { "file": "song.ogg", "title": "Song", "volume": 0.8, "bpm": 120, "loop_end": 61.5 }
+//!     ]
+//! }
+//! ```
+//! Only `tracks` and each track's `file` are required.
+
+use nanoserde::DeJson;
+use crate::rng::Rng;
+
+pub const MANIFEST_FILE: &str = "playlist.json";
+
+/// What `next` does at the end of the playlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatMode {
+    /// Music stops after the last track
+    Off,
+    /// Keeps playing the same track
+    One,
+    /// Starts over from the first track
+    All,
+}
+
+impl RepeatMode {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(RepeatMode::Off),
+            "one" => Some(RepeatMode::One),
+            "all" => Some(RepeatMode::All),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackInfo {
+    /// Relative to the music folder
+    pub file: String,
+    pub title: String,
+    /// Multiplies the music bus, evens out tracks mastered at different levels
+    pub volume: f32,
+    pub bpm: Option<f32>,
+    /// Seconds into the file the loop ends and jumps back to the start, `None` loops the whole file
+    pub loop_end: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playlist {
+    pub tracks: Vec<TrackInfo>,
+    pub shuffle: bool,
+    pub repeat: RepeatMode,
+}
+
+// The file as written, checked and filled in by `Playlist::parse`
+#[allow(clippy::question_mark)] // in the code generated by DeJson
+mod json {
+    use nanoserde::DeJson;
+
+    #[derive(DeJson)]
+    pub struct Manifest {
+        pub shuffle: Option<bool>,
+        pub repeat: Option<String>,
+        pub tracks: Vec<Track>,
+    }
+
+    #[derive(DeJson)]
+    pub struct Track {
+        pub file: String,
+        pub title: Option<String>,
+        pub volume: Option<f32>,
+        pub bpm: Option<f32>,
+        pub loop_end: Option<f32>,
+    }
+}
+
+impl Playlist {
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let manifest = json::Manifest::deserialize_json(json).map_err(|e| format!("Invalid playlist: {}", e))?;
+
+        let repeat = match manifest.repeat.as_deref() {
+            Some(name) => RepeatMode::from_name(name).ok_or_else(|| format!("Unknown repeat mode {}", name))?,
+            None => RepeatMode::All,
+        };
+
+        let tracks = manifest
+            .tracks
+            .into_iter()
+            .map(|track| TrackInfo {
+                title: track.title.unwrap_or_else(|| track.file.clone()),
+                file: track.file,
+                volume: track.volume.unwrap_or(1.0).clamp(0.0, 1.0),
+                bpm: track.bpm,
+                loop_end: track.loop_end.filter(|&end| end > 0.0),
+            })
+            .collect::<Vec<_>>();
+
+        if tracks.is_empty() {
+            return Err("Playlist has no tracks".to_string());
+        }
+
+        Ok(Playlist {
+            tracks,
+            shuffle: manifest.shuffle.unwrap_or(false),
+            repeat,
+        })
+    }
+}
+
+/// Order tracks are played in, reshuffled every time it wraps around when shuffling
+pub struct PlayOrder {
+    order: Vec<usize>,
+    position: usize,
+    shuffle: bool,
+    repeat: RepeatMode,
+    rng: Rng,
+}
+
+impl PlayOrder {
+    pub fn new(len: usize, shuffle: bool, repeat: RepeatMode, seed: u64) -> Self {
+        let mut play_order = PlayOrder {
+            order: (0..len).collect(),
+            position: 0,
+            shuffle,
+            repeat,
+            rng: Rng::new(seed),
+        };
+        if shuffle {
+            play_order.reshuffle(None);
+        }
+        play_order
+    }
+
+    pub fn current(&self) -> usize {
+        self.order[self.position]
+    }
+
+    /// Every track starting with the current one, in the order they'll play until the next reshuffle
+    pub fn upcoming(&self) -> impl Iterator<Item = usize> + '_ {
+        let len = self.order.len();
+        (0..len).map(move |i| self.order[(self.position + i) % len])
+    }
+
+    /// Moves on and returns the next track, and whether the playlist started over.
+    /// `None` when the playlist is done.
+    pub fn advance(&mut self) -> Option<(usize, bool)> {
+        match self.repeat {
+            RepeatMode::One => return Some((self.current(), false)),
+            RepeatMode::Off if self.position + 1 == self.order.len() => return None,
+            _ => {}
+        }
+
+        self.position += 1;
+        let wrapped = self.position == self.order.len();
+        if wrapped {
+            self.position = 0;
+            if self.shuffle {
+                let last = self.order.last().copied();
+                self.reshuffle(last);
+            }
+        }
+        Some((self.current(), wrapped))
+    }
+
+    /// Fisher-Yates, avoiding `previous` as the first track so nothing plays twice in a row
+    fn reshuffle(&mut self, previous: Option<usize>) {
+        for i in (1..self.order.len()).rev() {
+            let j = self.rng.gen_range(0, i + 1);
+            self.order.swap(i, j);
+        }
+        if self.order.len() > 1 && previous == Some(self.order[0]) {
+            self.order.swap(0, 1);
+        }
+    }
+}
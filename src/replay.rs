@@ -0,0 +1,388 @@
+use macroquad::file::load_file;
+use crate::assets::{self, AssetManager};
+use crate::collision::{BirdShape, PipeShape};
+use crate::pipe_texture_atlas::MASK_SPRITE;
+use crate::rng::SeedMode;
+use crate::simulation::{GameScene, Hitboxes, SimEvent, Simulation, FIXED_DELTA};
+
+const MAGIC: &[u8; 4] = b"FBRP";
+/// Version 2 added the bird hitbox, version 1 replays were played with the default one
+const VERSION: u8 = 2;
+/// Playback gives up after ten minutes of fixed updates without a death
+const MAX_TICKS: u32 = 60 * 60 * 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayInput {
+    Flap,
+}
+
+impl ReplayInput {
+    fn to_byte(self) -> u8 {
+        match self {
+            ReplayInput::Flap => 0,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(ReplayInput::Flap),
+            _ => None,
+        }
+    }
+}
+
+/// A single run: its seed and bird hitbox, every buffered input by fixed-update index, and how it ended
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub bird_shape: BirdShape,
+    pub events: Vec<(u32, ReplayInput)>,
+    pub score: u32,
+    pub death_tick: u32,
+}
+
+impl Replay {
+    /// Layout: magic, version, seed, bird shape, score, death tick, event count,
+    /// then each event as a varint tick delta followed by its input byte.
+    /// The bird shape is a kind byte followed by its sizes as `f32`s.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(32 + self.events.len() * 2);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        write_bird_shape(&mut bytes, self.bird_shape);
+        write_varint(&mut bytes, self.score);
+        write_varint(&mut bytes, self.death_tick);
+        write_varint(&mut bytes, self.events.len() as u32);
+
+        let mut last_tick = 0;
+        for &(tick, input) in &self.events {
+            write_varint(&mut bytes, tick - last_tick);
+            bytes.push(input.to_byte());
+            last_tick = tick;
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, position: 0 };
+
+        if reader.take(4)? != MAGIC {
+            return Err("Not a replay file".to_string());
+        }
+        let version = reader.byte()?;
+        if version != 1 && version != VERSION {
+            return Err(format!("Unsupported replay version {}", version));
+        }
+
+        let seed = u64::from_le_bytes(reader.take(8)?.try_into().unwrap());
+        let bird_shape = if version == 1 { BirdShape::default() } else { reader.bird_shape()? };
+        let score = reader.varint()?;
+        let death_tick = reader.varint()?;
+        let count = reader.varint()?;
+
+        let mut events = Vec::new();
+        let mut tick = 0u32;
+        for _ in 0..count {
+            tick = tick
+                .checked_add(reader.varint()?)
+                .ok_or("Replay tick overflow")?;
+            let byte = reader.byte()?;
+            let input = ReplayInput::from_byte(byte)
+                .ok_or_else(|| format!("Unknown replay input {}", byte))?;
+            events.push((tick, input));
+        }
+
+        Ok(Replay { seed, bird_shape, events, score, death_tick })
+    }
+
+    pub async fn load(path: &str) -> Result<Self, String> {
+        let bytes = load_file(path)
+            .await
+            .map_err(|e| format!("Failed to load replay {}: {}", path, e))?;
+        Self::from_bytes(&bytes).map_err(|e| format!("Failed to read replay {}: {}", path, e))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_bytes())
+            .map_err(|e| format!("Failed to save replay {}: {}", path, e))
+    }
+
+    /// Runs the replay through a headless simulation with its own bird hitbox and returns (score, death tick)
+    pub fn simulate(&self, hitboxes: Hitboxes) -> Option<(u32, u32)> {
+        let hitboxes = Hitboxes { bird_shape: self.bird_shape, ..hitboxes };
+        let mut simulation = Simulation::new(hitboxes, SeedMode::Fixed(self.seed));
+        let mut playback = ReplayPlayback::new(self.clone());
+
+        // Bounded by steps rather than `tick`, which stays at 0 until a flap starts the run
+        for _ in 0..MAX_TICKS {
+            let tick = simulation.tick;
+            let flap = playback.flap_at(tick);
+            let events = simulation.step(FIXED_DELTA, flap);
+            if events.contains(&SimEvent::Died) {
+                return Some((simulation.world.score, tick));
+            }
+        }
+        None
+    }
+}
+
+/// Builds a replay from the inputs of the run in progress
+#[derive(Default)]
+pub struct ReplayRecorder {
+    current: Option<Replay>,
+}
+
+impl ReplayRecorder {
+    /// Call after every fixed update with the tick the step ran at.
+    /// Returns the finished replay once the run ends.
+    pub fn record(&mut self, simulation: &Simulation, tick: u32, flap: bool, events: &[SimEvent]) -> Option<Replay> {
+        for event in events {
+            // Resuming from a pause also changes to `Playing` but carries on the same run
+            if let SimEvent::SceneChanged { from: GameScene::StartScreen, to: GameScene::Playing } = event {
+                self.current = Some(Replay {
+                    seed: simulation.seed,
+                    bird_shape: simulation.player.shape,
+                    events: Vec::new(),
+                    score: 0,
+                    death_tick: 0,
+                });
+            }
+        }
+
+        let replay = self.current.as_mut()?;
+        if flap {
+            replay.events.push((tick, ReplayInput::Flap));
+        }
+
+        if events.contains(&SimEvent::Died) {
+            let mut replay = self.current.take()?;
+            replay.score = simulation.world.score;
+            replay.death_tick = tick;
+            return Some(replay);
+        }
+        None
+    }
+}
+
+/// Feeds recorded inputs back by fixed-update index
+pub struct ReplayPlayback {
+    replay: Replay,
+    next_event: usize,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayback { replay, next_event: 0 }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.replay.seed
+    }
+
+    /// The hitbox the run was recorded with, playback has to use the same one
+    pub fn bird_shape(&self) -> BirdShape {
+        self.replay.bird_shape
+    }
+
+    pub fn flap_at(&mut self, tick: u32) -> bool {
+        let mut flap = false;
+        while let Some(&(event_tick, input)) = self.replay.events.get(self.next_event) {
+            if event_tick > tick {
+                break;
+            }
+            if event_tick == tick && input == ReplayInput::Flap {
+                flap = true;
+            }
+            self.next_event += 1;
+        }
+        flap
+    }
+
+    pub fn expected(&self) -> (u32, u32) {
+        (self.replay.score, self.replay.death_tick)
+    }
+}
+
+/// Headless check for `--verify-replay <file>`, returns the process exit code
+#[cfg(not(target_arch = "wasm32"))]
+pub fn verify_file(path: &str) -> i32 {
+    let replay = match std::fs::read(path)
+        .map_err(|e| format!("Failed to load replay {}: {}", path, e))
+        .and_then(|bytes| Replay::from_bytes(&bytes))
+    {
+        Ok(replay) => replay,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+
+    // Same pipe mask as the game, so the rules match what was recorded.
+    // Without it the result would be a false mismatch.
+    let hitboxes = match AssetManager::new(assets::ROOT).sprite_mask(MASK_SPRITE) {
+        Ok(mask) => Hitboxes { pipe_shape: PipeShape::Mask(mask), ..Hitboxes::default() },
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+
+    match replay.simulate(hitboxes) {
+        Some((score, death_tick)) if score == replay.score && death_tick == replay.death_tick => {
+            println!("Replay OK: seed {}, score {}, death tick {}", replay.seed, score, death_tick);
+            0
+        }
+        Some((score, death_tick)) => {
+            println!(
+                "Replay MISMATCH: expected score {} at tick {}, got score {} at tick {}",
+                replay.score, replay.death_tick, score, death_tick
+            );
+            1
+        }
+        None => {
+            println!("Replay MISMATCH: no death within {} ticks", MAX_TICKS);
+            1
+        }
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn write_bird_shape(bytes: &mut Vec<u8>, shape: BirdShape) {
+    let (kind, sizes): (u8, &[f32]) = match shape {
+        BirdShape::InsetRect { inset } => (0, &[inset]),
+        BirdShape::Circle { radius } => (1, &[radius]),
+        BirdShape::Capsule { half_length, radius } => (2, &[half_length, radius]),
+    };
+    bytes.push(kind);
+    for size in sizes {
+        bytes.extend_from_slice(&size.to_le_bytes());
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.position + len;
+        let slice = self.bytes.get(self.position..end).ok_or("Replay file is truncated")?;
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn bird_shape(&mut self) -> Result<BirdShape, String> {
+        match self.byte()? {
+            0 => Ok(BirdShape::InsetRect { inset: self.f32()? }),
+            1 => Ok(BirdShape::Circle { radius: self.f32()? }),
+            2 => Ok(BirdShape::Capsule { half_length: self.f32()?, radius: self.f32()? }),
+            kind => Err(format!("Unknown bird hitbox {} in replay", kind)),
+        }
+    }
+
+    fn varint(&mut self) -> Result<u32, String> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7F) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Invalid varint in replay".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays a run flapping at `flap_ticks` and returns its replay
+    fn record(seed: u64, bird_shape: BirdShape, flap_ticks: &[u32]) -> Replay {
+        record_with_pause(seed, bird_shape, flap_ticks, None)
+    }
+
+    /// Same as `record`, pausing before step `pause_at` and asking to resume right away.
+    /// Like the game, nothing flaps while paused.
+    fn record_with_pause(seed: u64, bird_shape: BirdShape, flap_ticks: &[u32], pause_at: Option<u32>) -> Replay {
+        let hitboxes = Hitboxes { bird_shape, ..Hitboxes::default() };
+        let mut simulation = Simulation::new(hitboxes, SeedMode::Fixed(seed));
+        let mut recorder = ReplayRecorder::default();
+        for step in 0..MAX_TICKS {
+            if pause_at == Some(step) {
+                simulation.pause();
+                simulation.resume();
+            }
+            let tick = simulation.tick;
+            let flap = simulation.scene != GameScene::Paused && flap_ticks.contains(&tick);
+            let events = simulation.step(FIXED_DELTA, flap);
+            if let Some(replay) = recorder.record(&simulation, tick, flap, &events) {
+                return replay;
+            }
+        }
+        panic!("run never ended");
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let replay = Replay {
+            seed: u64::MAX - 3,
+            bird_shape: BirdShape::Circle { radius: 11.0 },
+            events: vec![(0, ReplayInput::Flap), (40, ReplayInput::Flap), (300, ReplayInput::Flap)],
+            score: 12,
+            death_tick: 1000,
+        };
+        assert_eq!(Replay::from_bytes(&replay.to_bytes()), Ok(replay));
+    }
+
+    #[test]
+    fn recorded_run_plays_back_the_same() {
+        let replay = record(7, BirdShape::default(), &[0, 45, 90, 135, 180]);
+        assert_eq!(replay.events.first(), Some(&(0, ReplayInput::Flap)));
+
+        let replay = Replay::from_bytes(&replay.to_bytes()).unwrap();
+        assert_eq!(replay.simulate(Hitboxes::default()), Some((replay.score, replay.death_tick)));
+    }
+
+    #[test]
+    fn replay_keeps_its_bird_hitbox() {
+        let circle = BirdShape::Circle { radius: 11.0 };
+        let replay = record(7, circle, &[0, 45, 90, 135, 180]);
+        assert_eq!(replay.bird_shape, circle);
+
+        // Verified with the default hitbox, as `--verify-replay` does
+        let replay = Replay::from_bytes(&replay.to_bytes()).unwrap();
+        assert_eq!(replay.simulate(Hitboxes::default()), Some((replay.score, replay.death_tick)));
+    }
+
+    #[test]
+    fn pausing_keeps_the_replay() {
+        let replay = record_with_pause(7, BirdShape::default(), &[0, 25, 70, 115], Some(30));
+        assert_eq!(replay.events.first(), Some(&(0, ReplayInput::Flap)));
+        assert_eq!(replay.events.get(1), Some(&(25, ReplayInput::Flap)));
+        assert_eq!(replay.simulate(Hitboxes::default()), Some((replay.score, replay.death_tick)));
+    }
+
+    #[test]
+    fn replay_without_a_start_gives_up() {
+        let replay = Replay { seed: 7, bird_shape: BirdShape::default(), events: Vec::new(), score: 0, death_tick: 0 };
+        assert_eq!(replay.simulate(Hitboxes::default()), None);
+    }
+}
@@ -0,0 +1,55 @@
+/// Small seedable generator (SplitMix64) so runs can be reproduced from a seed.
+/// Kept separate from `macroquad::rand`, which is a single global generator.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Generates a number in `low..high` (high exclusive)
+    pub fn gen_range(&mut self, low: usize, high: usize) -> usize {
+        let span = (high - low) as u64;
+        low + (self.next_u64() % span) as usize
+    }
+}
+
+/// Where the seed for each run comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedMode {
+    /// Every run uses this seed (replays, daily challenges, regression tests)
+    Fixed(u64),
+    /// Each run draws a fresh seed from a session generator started at this seed
+    Session(u64),
+}
+
+impl SeedMode {
+    pub fn seed(&self) -> u64 {
+        match self {
+            SeedMode::Fixed(seed) | SeedMode::Session(seed) => *seed,
+        }
+    }
+}
+
+/// Numbers are used as-is, anything else (e.g. a date for a daily challenge) is hashed
+pub fn seed_from_str(value: &str) -> u64 {
+    if let Ok(seed) = value.trim().parse::<u64>() {
+        return seed;
+    }
+
+    // FNV-1a
+    value.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
@@ -0,0 +1,95 @@
+use crate::logging;
+use crate::storage::Storage;
+use crate::theme::DEFAULT_THEME;
+
+const KEY: &str = "settings";
+/// Volume change per step in the settings menu
+pub const VOLUME_STEP: f32 = 0.1;
+
+/// Player options, saved as `name = value` lines under the `settings` storage key
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub muted: bool,
+    /// Folder of the theme under `assets/themes`
+    pub theme: String,
+    /// Name of one of the theme's birds, `None` picks a random one every session
+    pub bird_color: Option<String>,
+    pub debug_overlay: bool,
+    /// Keeps the bird level and still, and other decorative movement to a minimum
+    pub reduced_motion: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            muted: false,
+            theme: DEFAULT_THEME.to_string(),
+            bird_color: None,
+            debug_overlay: false,
+            reduced_motion: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Unknown names and invalid values are skipped, so old or hand-edited files still load
+    pub fn parse(text: &str) -> Self {
+        let mut settings = Settings::default();
+
+        for line in text.lines() {
+            let Some((name, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            let volume = || value.parse::<f32>().ok().map(|v| v.clamp(0.0, 1.0));
+            let flag = || value.parse::<bool>().ok();
+
+            match name.trim() {
+                "master_volume" => settings.master_volume = volume().unwrap_or(settings.master_volume),
+                "music_volume" => settings.music_volume = volume().unwrap_or(settings.music_volume),
+                "sfx_volume" => settings.sfx_volume = volume().unwrap_or(settings.sfx_volume),
+                "muted" => settings.muted = flag().unwrap_or(settings.muted),
+                "theme" => {
+                    if !value.is_empty() {
+                        settings.theme = value.to_string();
+                    }
+                }
+                "bird_color" => settings.bird_color = Some(value).filter(|&name| name != "random").map(str::to_string),
+                "debug_overlay" => settings.debug_overlay = flag().unwrap_or(settings.debug_overlay),
+                "reduced_motion" => settings.reduced_motion = flag().unwrap_or(settings.reduced_motion),
+                other => logging::warning!(Game, "Unknown setting {}", other),
+            }
+        }
+        settings
+    }
+
+    pub fn to_text(&self) -> String {
+        format!(
+            "master_volume = {:.1}\nmusic_volume = {:.1}\nsfx_volume = {:.1}\nmuted = {}\ntheme = {}\nbird_color = {}\ndebug_overlay = {}\nreduced_motion = {}\n",
+            self.master_volume,
+            self.music_volume,
+            self.sfx_volume,
+            self.muted,
+            self.theme,
+            self.bird_color.as_deref().unwrap_or("random"),
+            self.debug_overlay,
+            self.reduced_motion,
+        )
+    }
+
+    pub fn load(storage: &dyn Storage) -> Self {
+        storage.get(KEY).map(|text| Self::parse(&text)).unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &mut dyn Storage) {
+        if let Err(e) = storage.set(KEY, &self.to_text()) {
+            logging::warning!(Game, "{}", e);
+        }
+    }
+}
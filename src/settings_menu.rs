@@ -0,0 +1,204 @@
+use macroquad::prelude::*;
+use crate::input::{Action, Input};
+use crate::settings::{Settings, VOLUME_STEP};
+use crate::theme::ThemeInfo;
+use crate::SCREEN_WIDTH;
+
+/// Button on the start screen that opens the menu
+pub const OPEN_BUTTON: Rect = Rect { x: SCREEN_WIDTH - 90.0, y: 10.0, w: 80.0, h: 28.0 };
+
+const PANEL_X: f32 = 20.0;
+const PANEL_WIDTH: f32 = SCREEN_WIDTH - 40.0;
+const TITLE_Y: f32 = 110.0;
+const FIRST_ROW_Y: f32 = 150.0;
+const ROW_HEIGHT: f32 = 40.0;
+const FONT_SIZE: f32 = 20.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Mute,
+    Theme,
+    BirdColor,
+    DebugOverlay,
+    ReducedMotion,
+    Back,
+}
+
+const ROWS: [Row; 9] = [
+    Row::MasterVolume,
+    Row::MusicVolume,
+    Row::SfxVolume,
+    Row::Mute,
+    Row::Theme,
+    Row::BirdColor,
+    Row::DebugOverlay,
+    Row::ReducedMotion,
+    Row::Back,
+];
+
+/// Options that depend on what's installed
+pub struct Choices<'a> {
+    pub themes: &'a [ThemeInfo],
+    /// Birds of the current theme
+    pub birds: &'a [String],
+}
+
+/// What the menu did with this update's input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuOutcome {
+    None,
+    Changed,
+    Close,
+}
+
+/// Settings scene: up/down picks a row, left/right or confirm changes it.
+/// Tapping the left or right half of a row does the same on touch screens.
+#[derive(Default)]
+pub struct SettingsMenu {
+    selected: usize,
+}
+
+impl SettingsMenu {
+    pub fn update(
+        &mut self,
+        input: &Input,
+        pointer: Option<Vec2>,
+        settings: &mut Settings,
+        choices: &Choices,
+    ) -> MenuOutcome {
+        if input.pressed(Action::Back) {
+            return MenuOutcome::Close;
+        }
+        if input.pressed(Action::MenuUp) {
+            self.selected = (self.selected + ROWS.len() - 1) % ROWS.len();
+        }
+        if input.pressed(Action::MenuDown) {
+            self.selected = (self.selected + 1) % ROWS.len();
+        }
+
+        let mut step = 0;
+        if input.pressed(Action::MenuLeft) {
+            step = -1;
+        }
+        if input.pressed(Action::MenuRight) || input.pressed(Action::Confirm) {
+            step = 1;
+        }
+        if let Some(pointer) = pointer
+            && let Some(i) = (0..ROWS.len()).find(|&i| row_rect(i).contains(pointer))
+        {
+            self.selected = i;
+            step = if pointer.x < SCREEN_WIDTH / 2.0 { -1 } else { 1 };
+        }
+
+        if step == 0 {
+            return MenuOutcome::None;
+        }
+        match ROWS[self.selected] {
+            Row::MasterVolume => settings.master_volume = change_volume(settings.master_volume, step),
+            Row::MusicVolume => settings.music_volume = change_volume(settings.music_volume, step),
+            Row::SfxVolume => settings.sfx_volume = change_volume(settings.sfx_volume, step),
+            Row::Mute => settings.muted = !settings.muted,
+            Row::Theme => {
+                let ids: Vec<String> = choices.themes.iter().map(|theme| theme.id.clone()).collect();
+                if let Some(theme) = cycle(&ids, Some(&settings.theme), step, false) {
+                    settings.theme = theme;
+                }
+            }
+            Row::BirdColor => settings.bird_color = cycle(choices.birds, settings.bird_color.as_ref(), step, true),
+            Row::DebugOverlay => settings.debug_overlay = !settings.debug_overlay,
+            Row::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+            Row::Back => return MenuOutcome::Close,
+        }
+        MenuOutcome::Changed
+    }
+
+    pub fn draw(&self, settings: &Settings, choices: &Choices) {
+        let panel_height = FIRST_ROW_Y - TITLE_Y + ROWS.len() as f32 * ROW_HEIGHT + 50.0;
+        draw_rectangle(PANEL_X, TITLE_Y - 40.0, PANEL_WIDTH, panel_height, Color::new(0.0, 0.0, 0.0, 0.75));
+
+        let title = "Settings";
+        let title_dimensions = measure_text(title, None, 32, 1.0);
+        draw_text(title, (SCREEN_WIDTH - title_dimensions.width) / 2.0, TITLE_Y, 32.0, WHITE);
+
+        for (i, row) in ROWS.iter().enumerate() {
+            let rect = row_rect(i);
+            let color = if i == self.selected { GOLD } else { WHITE };
+            if i == self.selected {
+                draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, GOLD);
+            }
+
+            let text_y = rect.y + rect.h / 2.0 + FONT_SIZE / 3.0;
+            let (label, value) = describe(*row, settings, choices);
+            draw_text(label, rect.x + 10.0, text_y, FONT_SIZE, color);
+            if let Some(value) = value {
+                let value = format!("< {} >", value);
+                let value_dimensions = measure_text(&value, None, FONT_SIZE as u16, 1.0);
+                draw_text(&value, rect.right() - 10.0 - value_dimensions.width, text_y, FONT_SIZE, color);
+            }
+        }
+    }
+}
+
+/// Draws the button that opens the menu from the start screen
+pub fn draw_open_button() {
+    let rect = OPEN_BUTTON;
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::new(0.0, 0.0, 0.0, 0.5));
+    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, WHITE);
+    let text = "Settings";
+    let dimensions = measure_text(text, None, 18, 1.0);
+    draw_text(text, rect.x + (rect.w - dimensions.width) / 2.0, rect.y + rect.h / 2.0 + 6.0, 18.0, WHITE);
+}
+
+fn row_rect(index: usize) -> Rect {
+    Rect::new(PANEL_X + 10.0, FIRST_ROW_Y + index as f32 * ROW_HEIGHT, PANEL_WIDTH - 20.0, ROW_HEIGHT - 6.0)
+}
+
+fn describe(row: Row, settings: &Settings, choices: &Choices) -> (&'static str, Option<String>) {
+    let on_off = |value: bool| Some(if value { "On" } else { "Off" }.to_string());
+    match row {
+        Row::MasterVolume => ("Volume", Some(format!("{:.0}%", settings.master_volume * 100.0))),
+        Row::MusicVolume => ("Music", Some(format!("{:.0}%", settings.music_volume * 100.0))),
+        Row::SfxVolume => ("Sounds", Some(format!("{:.0}%", settings.sfx_volume * 100.0))),
+        Row::Mute => ("Mute", on_off(settings.muted)),
+        Row::Theme => {
+            let theme = choices.themes.iter().find(|theme| theme.id == settings.theme);
+            ("Theme", Some(theme.map_or(settings.theme.clone(), |theme| theme.name.clone())))
+        }
+        Row::BirdColor => ("Bird", Some(settings.bird_color.as_deref().map_or("Random".to_string(), capitalize))),
+        Row::DebugOverlay => ("Debug overlay", on_off(settings.debug_overlay)),
+        Row::ReducedMotion => ("Reduced motion", on_off(settings.reduced_motion)),
+        Row::Back => ("Back", None),
+    }
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+}
+
+fn change_volume(volume: f32, step: i32) -> f32 {
+    // Count in whole steps so repeated changes don't drift
+    let steps = (1.0 / VOLUME_STEP).round();
+    ((volume / VOLUME_STEP).round() + step as f32).clamp(0.0, steps) * VOLUME_STEP
+}
+
+/// Steps through `options`, with `None` before the first when `with_none`
+fn cycle(options: &[String], current: Option<&String>, step: i32, with_none: bool) -> Option<String> {
+    let offset = with_none as i32;
+    let count = options.len() as i32 + offset;
+    if count == 0 {
+        return None;
+    }
+    let position = current
+        .and_then(|current| options.iter().position(|option| option == current))
+        .map_or(0, |i| i as i32 + offset);
+    match (position + step).rem_euclid(count) - offset {
+        -1 => None,
+        i => Some(options[i as usize].clone()),
+    }
+}
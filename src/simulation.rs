@@ -0,0 +1,294 @@
+use crate::collision::{BirdShape, PipeShape};
+use crate::components::{Node, Size};
+use crate::difficulty::DifficultyCurve;
+use crate::pipe_placement::PipePlacer;
+use crate::player::{Player, GRAVITY};
+use crate::rng::{Rng, SeedMode};
+use crate::world::World;
+use crate::logging;
+
+pub const FIXED_DELTA: f32 = 1.0 / 60.0; // Fixed timestep for consistent physics
+/// Seconds between asking to resume and the run continuing
+pub const RESUME_COUNTDOWN: f32 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameScene {
+    StartScreen,
+    Playing,
+    Paused,
+    GameOver,
+    /// Settings menu opened from the start screen, the simulation waits
+    Settings,
+}
+
+/// Hitbox dimensions and shapes the rules run against.
+/// The defaults match the bundled sprites so the simulation can run without loading them,
+/// except for the pipe mask which needs the sprite (see `AssetManager::sprite_mask`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hitboxes {
+    pub bird: Size,
+    pub bird_shape: BirdShape,
+    pub pipe: Size,
+    pub pipe_shape: PipeShape,
+    pub base: Size,
+}
+
+impl Default for Hitboxes {
+    fn default() -> Self {
+        Hitboxes {
+            bird: Size::new(34.0, 24.0),
+            bird_shape: BirdShape::default(),
+            pipe: Size::new(52.0, 320.0),
+            pipe_shape: PipeShape::default(),
+            base: Size::new(336.0, 112.0),
+        }
+    }
+}
+
+/// Things that happened during a step, for the caller to play sounds or music on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimEvent {
+    SceneChanged { from: GameScene, to: GameScene },
+    Flapped,
+    Scored,
+    Died,
+}
+
+/// The game rules without any rendering, audio or input polling.
+/// Step it with `FIXED_DELTA` and whether a flap was pressed since the last step.
+pub struct Simulation {
+    pub scene: GameScene,
+    pub world: World,
+    pub player: Player,
+    /// Seed of the current run, the same seed always yields the same pipes
+    pub seed: u64,
+    /// Fixed-update index within the current run, 0 is the step that starts it
+    pub tick: u32,
+    /// Seconds left before a paused run continues, `None` until resuming is asked for
+    pub resume_countdown: Option<f32>,
+    seeder: Option<Rng>,
+}
+
+impl Simulation {
+    pub fn new(hitboxes: Hitboxes, seed_mode: SeedMode) -> Self {
+        let seed = seed_mode.seed();
+        let seeder = match seed_mode {
+            SeedMode::Fixed(_) => None,
+            SeedMode::Session(_) => Some(Rng::new(seed)),
+        };
+
+        let player = Player::new(hitboxes.bird, hitboxes.bird_shape);
+        let pipe_placer = PipePlacer::new(player.jump_force, GRAVITY, hitboxes.bird, hitboxes.pipe.width);
+
+        Simulation {
+            scene: GameScene::StartScreen,
+            world: World::new(hitboxes.pipe, hitboxes.pipe_shape, hitboxes.base, pipe_placer, seed, DifficultyCurve::default()),
+            player,
+            seed,
+            tick: 0,
+            resume_countdown: None,
+            seeder,
+        }
+    }
+
+    fn change_scene(&mut self, to: GameScene, events: &mut Vec<SimEvent>) {
+        logging::info!(Scene, "{:?} -> {:?}", self.scene, to);
+        events.push(SimEvent::SceneChanged { from: self.scene, to });
+        self.scene = to;
+    }
+
+    /// Freezes a run in progress, also cancels a resume countdown
+    pub fn pause(&mut self) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        match self.scene {
+            GameScene::Playing => self.change_scene(GameScene::Paused, &mut events),
+            GameScene::Paused => self.resume_countdown = None,
+            GameScene::StartScreen | GameScene::GameOver | GameScene::Settings => {}
+        }
+        events
+    }
+
+    pub fn open_settings(&mut self) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        if self.scene == GameScene::StartScreen {
+            self.change_scene(GameScene::Settings, &mut events);
+        }
+        events
+    }
+
+    pub fn close_settings(&mut self) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        if self.scene == GameScene::Settings {
+            self.change_scene(GameScene::StartScreen, &mut events);
+        }
+        events
+    }
+
+    /// Starts the countdown back to `Playing`, steps keep running it down
+    pub fn resume(&mut self) {
+        if self.scene == GameScene::Paused && self.resume_countdown.is_none() {
+            self.resume_countdown = Some(RESUME_COUNTDOWN);
+        }
+    }
+
+    /// Paused steps do not advance `tick`, so pausing never changes how a replay plays out
+    pub fn step(&mut self, dt: f32, flap: bool) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+
+        match self.scene {
+            GameScene::StartScreen => {
+                if flap {
+                    self.change_scene(GameScene::Playing, &mut events);
+                }
+            }
+            GameScene::Playing => {
+            }
+            GameScene::Paused => {
+                if let Some(remaining) = self.resume_countdown {
+                    let remaining = remaining - dt;
+                    if remaining > 0.0 {
+                        self.resume_countdown = Some(remaining);
+                    } else {
+                        self.resume_countdown = None;
+                        self.change_scene(GameScene::Playing, &mut events);
+                    }
+                }
+            }
+            GameScene::Settings => {}
+            GameScene::GameOver => {
+                if flap {
+                    self.change_scene(GameScene::StartScreen, &mut events);
+                    // Only here, the start screen restarts the player every step and the wings would never move
+                    self.player.wing_animation.reset();
+                    if let Some(seeder) = &mut self.seeder {
+                        self.seed = seeder.next_u64();
+                    }
+                    logging::info!(Scene, "Next run seed: {}", self.seed);
+                }
+            }
+        }
+
+        let player = &mut self.player;
+        let world = &mut self.world;
+
+        match self.scene {
+            GameScene::StartScreen => {
+                player.restart();
+                player.hover(dt);
+                world.restart(self.seed);
+                self.tick = 0;
+                return events;
+            }
+            GameScene::Playing => {
+                // Handle jump input (before physics update)
+                if flap {
+                    player.handle_jump();
+                    events.push(SimEvent::Flapped);
+                }
+
+                player.update(dt);
+                world.update(dt);
+
+                if world.player_passed_pipes(player) {
+                    events.push(SimEvent::Scored);
+                }
+
+                if world.touched(player) {
+                    player.dead();
+                    world.end();
+                    events.push(SimEvent::Died);
+                    self.change_scene(GameScene::GameOver, &mut events);
+                }
+            }
+            GameScene::Paused | GameScene::Settings => {
+                return events;
+            }
+            GameScene::GameOver => {
+                player.update(dt);
+            }
+        }
+
+        self.tick += 1;
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Steps a run that flaps every `flap_every` ticks until it ends or `steps` run out
+    fn run(seed: u64, flap_every: u32, steps: u32) -> (Simulation, Vec<SimEvent>) {
+        let mut simulation = Simulation::new(Hitboxes::default(), SeedMode::Fixed(seed));
+        let mut events = Vec::new();
+        for step in 0..steps {
+            events.extend(simulation.step(FIXED_DELTA, step % flap_every == 0));
+            if simulation.scene == GameScene::GameOver {
+                break;
+            }
+        }
+        (simulation, events)
+    }
+
+    fn pipe_positions(simulation: &Simulation) -> Vec<(f32, f32, f32)> {
+        simulation
+            .world
+            .pipes
+            .iter()
+            .map(|(pipe1, pipe2)| (pipe1.position.x, pipe1.position.y, pipe2.position.y))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_pipes() {
+        let (first, first_events) = run(42, 45, 300);
+        let (second, second_events) = run(42, 45, 300);
+
+        assert!(!first.world.pipes.is_empty());
+        assert_eq!(pipe_positions(&first), pipe_positions(&second));
+        assert_eq!(first_events, second_events);
+        assert_eq!(first.tick, second.tick);
+    }
+
+    #[test]
+    fn flap_starts_a_run_and_death_ends_it() {
+        let mut simulation = Simulation::new(Hitboxes::default(), SeedMode::Fixed(7));
+        simulation.step(FIXED_DELTA, false);
+        assert_eq!(simulation.scene, GameScene::StartScreen);
+
+        let events = simulation.step(FIXED_DELTA, true);
+        assert!(events.contains(&SimEvent::SceneChanged { from: GameScene::StartScreen, to: GameScene::Playing }));
+        assert!(events.contains(&SimEvent::Flapped));
+
+        // Without flapping the bird falls onto the base
+        let mut died = false;
+        for _ in 0..600 {
+            if simulation.step(FIXED_DELTA, false).contains(&SimEvent::Died) {
+                died = true;
+                break;
+            }
+        }
+        assert!(died);
+        assert_eq!(simulation.scene, GameScene::GameOver);
+
+        simulation.step(FIXED_DELTA, true);
+        assert_eq!(simulation.scene, GameScene::StartScreen);
+        assert_eq!(wing_frames(&mut simulation, 120).len(), 3);
+    }
+
+    /// Distinct wing frames shown over `steps` idle steps
+    fn wing_frames(simulation: &mut Simulation, steps: u32) -> std::collections::HashSet<usize> {
+        (0..steps)
+            .map(|_| {
+                simulation.step(FIXED_DELTA, false);
+                simulation.player.wing_animation.current_frame()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wings_flap_on_the_start_screen() {
+        let mut simulation = Simulation::new(Hitboxes::default(), SeedMode::Fixed(7));
+        assert_eq!(wing_frames(&mut simulation, 120).len(), 3);
+    }
+}
@@ -1,4 +1,6 @@
-use macroquad::audio::{load_sound, play_sound_once, Sound};
+use macroquad::audio::{play_sound, PlaySoundParams, Sound};
+use crate::assets::AssetManager;
+use crate::audio_mixer::{AudioMixer, Bus};
 
 pub struct SoundEffects {
     pub death_sound: Sound,
@@ -8,44 +10,37 @@ pub struct SoundEffects {
 }
 
 impl SoundEffects {
-    pub async fn new() -> Self {
-        let death_sound = load_sound("assets/audio/death-sound-trimmed.ogg")
-            .await
-            .expect("Failed to load death sound");
-
-        let hit_sound = load_sound("assets/audio/hit-trimmed.ogg")
-            .await
-            .expect("Failed to load hit sound");
-
-        let point_sound = load_sound("assets/audio/point-trimmed.ogg")
-            .await
-            .expect("Failed to load point sound");
-
-        let wing_sound = load_sound("assets/audio/wing-trimmed.ogg")
-            .await
-            .expect("Failed to load wing sound");
+    pub fn new(assets: &AssetManager) -> Result<Self, String> {
+        let sound = |file| assets.sound(file).map(|handle| assets.get(handle).clone());
+        Ok(Self {
+            death_sound: sound("audio/death-sound-trimmed.ogg")?,
+            hit_sound: sound("audio/hit-trimmed.ogg")?,
+            point_sound: sound("audio/point-trimmed.ogg")?,
+            wing_sound: sound("audio/wing-trimmed.ogg")?,
+        })
+    }
 
-        Self {
-            death_sound,
-            hit_sound,
-            point_sound,
-            wing_sound,
+    /// Plays on the effects bus, skipped entirely when it is silent
+    fn play(&self, sound: &Sound, mixer: &AudioMixer) {
+        let volume = mixer.volume(Bus::Sfx);
+        if volume > 0.0 {
+            play_sound(sound, PlaySoundParams { looped: false, volume });
         }
     }
 
-    pub fn play_death(&self) {
-        play_sound_once(&self.death_sound);
+    pub fn play_death(&self, mixer: &AudioMixer) {
+        self.play(&self.death_sound, mixer);
     }
 
-    pub fn play_hit(&self) {
-        play_sound_once(&self.hit_sound);
+    pub fn play_hit(&self, mixer: &AudioMixer) {
+        self.play(&self.hit_sound, mixer);
     }
 
-    pub fn play_point(&self) {
-        play_sound_once(&self.point_sound);
+    pub fn play_point(&self, mixer: &AudioMixer) {
+        self.play(&self.point_sound, mixer);
     }
 
-    pub fn play_wing(&self) {
-        play_sound_once(&self.wing_sound);
+    pub fn play_wing(&self, mixer: &AudioMixer) {
+        self.play(&self.wing_sound, mixer);
     }
 }
\ No newline at end of file
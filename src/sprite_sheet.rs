@@ -0,0 +1,121 @@
+//! Sprites packed into one texture by `build.rs`, with a frame list next to it:
+//!
+//! ```json
+//! { "width": 1016, "height": 650, "frames": { "base": { "x": 0, "y": 513, "w": 336, "h": 112 } } }
+//! ```
+
+use std::collections::HashMap;
+use macroquad::color::WHITE;
+use macroquad::math::Rect;
+use macroquad::texture::{draw_texture_ex, DrawTextureParams, Texture2D};
+use nanoserde::DeJson;
+
+/// Packed sheet and frame list, relative to the asset root. They are built into the executable, see `assets::embedded`.
+pub const SHEET_IMAGE: &str = "atlas/sprites.png";
+pub const SHEET_FRAMES: &str = "atlas/sprites.json";
+
+/// Part of a texture, drawn through `DrawTextureParams::source` so sprites on one sheet share a texture
+#[derive(Debug, Clone)]
+pub struct Sprite {
+    pub texture: Texture2D,
+    pub source: Rect,
+}
+
+impl Sprite {
+    /// The whole texture, for sprites loaded from their own file
+    pub fn whole(texture: Texture2D) -> Self {
+        let source = Rect::new(0.0, 0.0, texture.width(), texture.height());
+        Sprite { texture, source }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.source.w
+    }
+
+    pub fn height(&self) -> f32 {
+        self.source.h
+    }
+
+    /// Rows `top` to `bottom` of the sprite, counted from its own top
+    pub fn rows(&self, top: f32, bottom: f32) -> Sprite {
+        let source = Rect::new(self.source.x, self.source.y + top, self.source.w, bottom - top);
+        Sprite { texture: self.texture.clone(), source }
+    }
+
+    /// Draws untinted, `params.source` is replaced by the sprite's frame
+    pub fn draw(&self, x: f32, y: f32, params: DrawTextureParams) {
+        draw_texture_ex(
+            &self.texture,
+            x,
+            y,
+            WHITE,
+            DrawTextureParams {
+                source: Some(self.source),
+                ..params
+            },
+        );
+    }
+}
+
+/// Frame rects of a packed sheet, by sprite name
+pub struct SpriteSheet {
+    texture: Texture2D,
+    frames: HashMap<String, Rect>,
+}
+
+#[allow(clippy::question_mark)] // in the code generated by DeJson
+mod json {
+    use std::collections::HashMap;
+    use nanoserde::DeJson;
+
+    #[derive(DeJson)]
+    pub struct Sheet {
+        pub width: f32,
+        pub height: f32,
+        pub frames: HashMap<String, Frame>,
+    }
+
+    #[derive(DeJson)]
+    pub struct Frame {
+        pub x: f32,
+        pub y: f32,
+        pub w: f32,
+        pub h: f32,
+    }
+}
+
+impl SpriteSheet {
+    pub fn parse(texture: Texture2D, json: &str) -> Result<Self, String> {
+        let sheet = json::Sheet::deserialize_json(json).map_err(|e| format!("Invalid sprite sheet: {}", e))?;
+        if sheet.width != texture.width() || sheet.height != texture.height() {
+            return Err(format!(
+                "Sprite sheet is {}x{} but its frames are for {}x{}, rebuild to regenerate it",
+                texture.width(),
+                texture.height(),
+                sheet.width,
+                sheet.height
+            ));
+        }
+
+        let frames = sheet
+            .frames
+            .into_iter()
+            .map(|(name, frame)| (name, Rect::new(frame.x, frame.y, frame.w, frame.h)))
+            .collect();
+        Ok(SpriteSheet { texture, frames })
+    }
+
+    /// Rect of `name` in a frame list, for reading the sheet's pixels without its texture
+    pub fn find_frame(json: &str, name: &str) -> Result<Rect, String> {
+        let sheet = json::Sheet::deserialize_json(json).map_err(|e| format!("Invalid sprite sheet: {}", e))?;
+        let frame = sheet.frames.get(name).ok_or_else(|| format!("No sprite named {}", name))?;
+        Ok(Rect::new(frame.x, frame.y, frame.w, frame.h))
+    }
+
+    pub fn sprite(&self, name: &str) -> Option<Sprite> {
+        self.frames.get(name).map(|&source| Sprite {
+            texture: self.texture.clone(),
+            source,
+        })
+    }
+}
@@ -0,0 +1,106 @@
+//! Small key-value store for anything that should survive a restart.
+//! Native builds write one file per key under the user's data directory,
+//! the web build uses the browser's localStorage.
+
+pub trait Storage {
+    fn get(&self, key: &str) -> Option<String>;
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String>;
+}
+
+/// Opens the storage for this platform, falling back to memory when there is nowhere to write
+pub fn open() -> Box<dyn Storage> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        Box::new(LocalStorage)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        match FileStorage::in_data_dir() {
+            Some(storage) => Box::new(storage),
+            None => {
+                crate::logging::warning!(Game, "No data directory found, nothing will be saved");
+                Box::new(MemoryStorage::default())
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage {
+    dir: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    pub fn new(dir: std::path::PathBuf) -> Self {
+        FileStorage { dir }
+    }
+
+    /// `%APPDATA%` on Windows, `~/Library/Application Support` on macOS,
+    /// `$XDG_DATA_HOME` or `~/.local/share` elsewhere
+    pub fn in_data_dir() -> Option<Self> {
+        use std::env::var_os;
+        use std::path::PathBuf;
+
+        let base = if cfg!(target_os = "windows") {
+            var_os("APPDATA").map(PathBuf::from)
+        } else if cfg!(target_os = "macos") {
+            var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+        } else {
+            var_os("XDG_DATA_HOME")
+                .map(PathBuf::from)
+                .or_else(|| var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        };
+
+        base.map(|base| Self::new(base.join("WebFlappyBird")))
+    }
+
+    fn path(&self, key: &str) -> std::path::PathBuf {
+        self.dir.join(format!("{}.txt", key))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FileStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        std::fs::read_to_string(self.path(key)).ok()
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(self.path(key), value))
+            .map_err(|e| format!("Failed to save {}: {}", key, e))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub struct LocalStorage;
+
+#[cfg(target_arch = "wasm32")]
+impl Storage for LocalStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        crate::platform::local_storage_get(&format!("WebFlappyBird.{}", key))
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        crate::platform::local_storage_set(&format!("WebFlappyBird.{}", key), value)
+    }
+}
+
+/// Keeps values for this session only
+#[derive(Default)]
+pub struct MemoryStorage {
+    values: std::collections::HashMap<String, String>,
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        self.values.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+//! Skin packs under `assets/themes`. `themes.json` lists them, each folder has a `theme.json`:
+//!
+//! ```json
+//! {
+//!     "birds": [{ "name": "yellow", "frames": ["yellowbird-downflap", "yellowbird-midflap", "yellowbird-upflap"] }],
+//!     "pipes": { "sprites": ["pipe-red", "pipe-green"], "every": 5 },
+//!     "backgrounds": { "sprites": ["background-day", "background-night"], "every": 10 },
+//!     "layers": [
+//!         { "rows": [0, 304], "speed": 0 },
+//!         { "rows": [304, 338], "speed": 0.1 },
+//!         { "sprites": ["hills.png"], "every": 1, "speed": 0.5 }
+//!     ],
+//!     "base": "base",
+//!     "digits": ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"]
+//! }
+//! ```
+//! Sprites are names on the packed sheet, or `.png` files in the theme's folder.
+//! Pipes and backgrounds move on to their next sprite every `every` points.
+//! `layers` is optional and drawn back to front instead of the plain background, each scrolling at
+//! `speed` times the pipes. Layers show `rows` of their sprites, which default to the backgrounds.
+//! Themes only change the look: hitboxes keep their default sizes and sprites are drawn to fit them.
+
+use nanoserde::DeJson;
+use crate::assets::AssetManager;
+use crate::background_texture_atlas::{BackgroundLayer, BackgroundTextureAtlas};
+use crate::base_texture_atlas::BaseTextureAtlas;
+use crate::bird_texture_atlas::BirdTextureAtlas;
+use crate::number_texture_atlas::NumberTextureAtlas;
+use crate::pipe_texture_atlas::PipeTextureAtlas;
+use crate::sprite_sheet::Sprite;
+
+pub const THEMES_FILE: &str = "themes/themes.json";
+/// The bundled sprites as they've always looked
+pub const DEFAULT_THEME: &str = "classic";
+
+/// An entry of `themes.json`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThemeInfo {
+    /// Folder under `assets/themes`, saved in the settings
+    pub id: String,
+    pub name: String,
+}
+
+/// Sprites that take turns as the score goes up
+pub struct SpriteCycle {
+    sprites: Vec<Sprite>,
+    every: u32,
+}
+
+impl SpriteCycle {
+    pub fn new(sprites: Vec<Sprite>, every: u32) -> Result<Self, String> {
+        if sprites.is_empty() {
+            return Err("Sprite cycle has no sprites".to_string());
+        }
+        Ok(SpriteCycle { sprites, every: every.max(1) })
+    }
+
+    pub fn for_score(&self, score: u32) -> &Sprite {
+        &self.sprites[(score / self.every) as usize % self.sprites.len()]
+    }
+}
+
+pub struct Theme {
+    pub id: String,
+    /// Bird names, as saved in the settings, and their frames
+    pub birds: Vec<(String, BirdTextureAtlas)>,
+    pub pipes: PipeTextureAtlas,
+    pub backgrounds: BackgroundTextureAtlas,
+    pub base: BaseTextureAtlas,
+    pub digits: NumberTextureAtlas,
+}
+
+#[allow(clippy::question_mark)] // in the code generated by DeJson
+mod json {
+    use nanoserde::DeJson;
+
+    #[derive(DeJson)]
+    pub struct ThemeList {
+        pub themes: Vec<ThemeInfo>,
+    }
+
+    #[derive(DeJson)]
+    pub struct ThemeInfo {
+        pub id: String,
+        pub name: String,
+    }
+
+    #[derive(DeJson)]
+    pub struct Theme {
+        pub birds: Vec<Bird>,
+        pub pipes: Cycle,
+        pub backgrounds: Cycle,
+        pub layers: Option<Vec<Layer>>,
+        pub base: String,
+        pub digits: Vec<String>,
+    }
+
+    #[derive(DeJson)]
+    pub struct Bird {
+        pub name: String,
+        pub frames: Vec<String>,
+    }
+
+    #[derive(DeJson)]
+    pub struct Layer {
+        pub sprites: Option<Vec<String>>,
+        pub every: Option<u32>,
+        pub rows: Option<Vec<f32>>,
+        pub speed: f32,
+    }
+
+    #[derive(DeJson)]
+    pub struct Cycle {
+        pub sprites: Vec<String>,
+        pub every: u32,
+    }
+}
+
+/// Every theme in `themes.json`
+pub async fn list(assets: &mut AssetManager) -> Result<Vec<ThemeInfo>, String> {
+    assets.load(&[THEMES_FILE.to_string()]).await?;
+    let text = assets.get(assets.handle::<String>(THEMES_FILE)?);
+    let list = json::ThemeList::deserialize_json(text).map_err(|e| format!("{}: {}", assets.path(THEMES_FILE), e))?;
+    Ok(list
+        .themes
+        .into_iter()
+        .map(|theme| ThemeInfo { id: theme.id, name: theme.name })
+        .collect())
+}
+
+impl Theme {
+    /// Loads `themes/<id>/theme.json` and any sprite files of its own
+    pub async fn load(assets: &mut AssetManager, id: &str) -> Result<Theme, String> {
+        let folder = format!("themes/{}", id);
+        let manifest_file = format!("{}/theme.json", folder);
+        assets.load(std::slice::from_ref(&manifest_file)).await?;
+        let text = assets.get(assets.handle::<String>(&manifest_file)?);
+        let manifest = json::Theme::deserialize_json(text).map_err(|e| format!("{}: {}", assets.path(&manifest_file), e))?;
+
+        let mut names: Vec<&String> = manifest.birds.iter().flat_map(|bird| &bird.frames).collect();
+        names.extend(&manifest.pipes.sprites);
+        names.extend(&manifest.backgrounds.sprites);
+        names.extend(manifest.layers.iter().flatten().flat_map(|layer| layer.sprites.iter().flatten()));
+        names.push(&manifest.base);
+        names.extend(&manifest.digits);
+        let files: Vec<String> = names
+            .into_iter()
+            .filter(|name| name.ends_with(".png"))
+            .map(|name| format!("{}/{}", folder, name))
+            .collect();
+        assets.load(&files).await?;
+
+        let theme = Self::build(assets, id, &folder, &manifest).map_err(|e| format!("Theme {}: {}", id, e))?;
+        Ok(theme)
+    }
+
+    fn build(assets: &AssetManager, id: &str, folder: &str, manifest: &json::Theme) -> Result<Theme, String> {
+        let sprite = |name: &String| {
+            if name.ends_with(".png") {
+                let handle = assets.texture(&format!("{}/{}", folder, name))?;
+                Ok(Sprite::whole(assets.get(handle).clone()))
+            } else {
+                assets.sprite(name)
+            }
+        };
+        let sprites = |names: &[String]| names.iter().map(sprite).collect::<Result<Vec<_>, _>>();
+
+        if manifest.birds.is_empty() {
+            return Err("no birds".to_string());
+        }
+        let mut birds = Vec::new();
+        for bird in &manifest.birds {
+            let frames: [Sprite; 3] = sprites(&bird.frames)?
+                .try_into()
+                .map_err(|_| format!("bird {} needs 3 frames: down, mid and up", bird.name))?;
+            birds.push((bird.name.clone(), BirdTextureAtlas::new(frames)));
+        }
+
+        let digits: [Sprite; 10] = sprites(&manifest.digits)?
+            .try_into()
+            .map_err(|_| "digits needs 10 sprites, 0 to 9".to_string())?;
+
+        let backgrounds = &manifest.backgrounds;
+        let layers = match &manifest.layers {
+            Some(layers) => layers
+                .iter()
+                .enumerate()
+                .map(|(i, layer)| {
+                    let names = layer.sprites.as_ref().unwrap_or(&backgrounds.sprites);
+                    let sprites = sprites(names)?;
+                    let rows = match layer.rows.as_deref() {
+                        None => None,
+                        Some(&[top, bottom])
+                            if 0.0 <= top && top < bottom && sprites.iter().all(|sprite| bottom <= sprite.height()) =>
+                        {
+                            Some((top, bottom))
+                        }
+                        Some(_) => return Err(format!("layer {} rows must be [top, bottom] within its sprites", i)),
+                    };
+                    Ok(BackgroundLayer {
+                        sprites: SpriteCycle::new(sprites, layer.every.unwrap_or(backgrounds.every))?,
+                        rows,
+                        speed: layer.speed,
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?,
+            None => vec![BackgroundLayer {
+                sprites: SpriteCycle::new(sprites(&backgrounds.sprites)?, backgrounds.every)?,
+                rows: None,
+                speed: 0.0,
+            }],
+        };
+        if layers.is_empty() {
+            return Err("no background layers".to_string());
+        }
+
+        Ok(Theme {
+            id: id.to_string(),
+            birds,
+            pipes: PipeTextureAtlas::new(SpriteCycle::new(sprites(&manifest.pipes.sprites)?, manifest.pipes.every)?),
+            backgrounds: BackgroundTextureAtlas::new(layers),
+            base: BaseTextureAtlas::new(sprite(&manifest.base)?),
+            digits: NumberTextureAtlas::new(digits),
+        })
+    }
+}
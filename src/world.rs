@@ -1,60 +1,78 @@
-use std::rc::Rc;
-use macroquad::color::WHITE;
-use macroquad::prelude::vec2;
-use macroquad::rand::gen_range;
-use macroquad::texture::{draw_texture_ex, DrawTextureParams};
-use crate::{SCREEN_WIDTH, SCREEN_HEIGHT};
-use crate::background_texture_atlas::{BackgroundTextureAtlas, BackgroundType};
+use crate::SCREEN_HEIGHT;
 use crate::base::Base;
-use crate::base_texture_atlas::BaseTextureAtlas;
-use crate::components::Node;
-use crate::number_texture_atlas::NumberTextureAtlas;
-use crate::pipe_texture_atlas::PipeTextureAtlas;
+use crate::collision::PipeShape;
+use crate::components::{Node, Size};
+use crate::difficulty::DifficultyCurve;
+use crate::parallax::Parallax;
+use crate::pipe_placement::PipePlacer;
 use crate::pipes::{Pipe, PipeLocation};
 use crate::player::Player;
+use crate::rng::Rng;
+use crate::logging;
 
 pub struct World {
     pub score: u32,
     pub timer: f32,
-    pub background_texture_atlas: BackgroundTextureAtlas,
-    pub pipe_texture_atlas: Rc<PipeTextureAtlas>,
+    pub pipe_size: Size,
+    pub pipe_shape: PipeShape,
     pub pipes: Vec<(Pipe, Pipe)>,
     pub pipe_spawn_time: f32,
-    pub number_texture_atlas: NumberTextureAtlas,
     pub velocity: u16,
     pub base: Base,
+    pub parallax: Parallax,
     pub last_pipe_location_index: usize,
+    pub last_gap_size: f32,
+    pub pipe_placer: PipePlacer,
+    pub rng: Rng,
+    pub difficulty: DifficultyCurve,
 }
 
-pub const VELOCITY: u16 = 130;
-
 impl World {
-    pub async fn new(background_texture_atlas: BackgroundTextureAtlas) -> Self {
-        let pipe_texture_atlas = Rc::new(PipeTextureAtlas::new().await);
-        let base_texture_atlas = BaseTextureAtlas::new().await;
-        let number_texture_atlas = NumberTextureAtlas::new().await;
-        let base = Base::new(base_texture_atlas, VELOCITY).await;
+    pub fn new(pipe_size: Size,
+               pipe_shape: PipeShape,
+               base_size: Size,
+               pipe_placer: PipePlacer,
+               seed: u64,
+               difficulty: DifficultyCurve) -> Self {
+        let level = difficulty.at(0);
+        let base = Base::new(base_size, level.speed);
         World {
             score: 0,
             timer: 0.0,
-            background_texture_atlas,
-            pipe_texture_atlas,
+            pipe_size,
+            pipe_shape,
             pipes: Vec::new(),
-            pipe_spawn_time: 2.0,
-            number_texture_atlas,
-            velocity: VELOCITY,
+            pipe_spawn_time: level.spawn_interval,
+            velocity: level.speed,
             base,
+            parallax: Parallax::new(level.speed),
             last_pipe_location_index: 4,  // Start at Mid
+            last_gap_size: level.gap_size,
+            pipe_placer,
+            rng: Rng::new(seed),
+            difficulty,
         }
     }
 
+    /// Moves pipes, base and background at `speed` so everything on screen scrolls together
+    fn set_velocity(&mut self, speed: u16) {
+        self.velocity = speed;
+        self.base.velocity = speed;
+        self.parallax.velocity = speed;
+        self.pipes.iter_mut().for_each(|(pipe1, pipe2)| {
+            pipe1.velocity = speed;
+            pipe2.velocity = speed;
+        });
+    }
+
     pub fn touched(&self, player: &Player) -> bool {
         // Check if player flew off screen (top or bottom)
         let off_screen = player.position.y < 0.0 || player.position.y > SCREEN_HEIGHT;
 
+        let bird = player.hitbox();
         self.pipes.iter().any(|(pipe1, pipe2)| {
-            pipe1.touched(player) || pipe2.touched(player)
-        }) || self.base.touched(player) || off_screen
+            pipe1.touched(&bird, &self.pipe_shape) || pipe2.touched(&bird, &self.pipe_shape)
+        }) || self.base.touched(&bird) || off_screen
     }
 
     pub fn end(&mut self) {
@@ -64,16 +82,27 @@ impl World {
         });
 
         self.base.stop();
+        self.parallax.stop();
     }
 
-    pub fn restart(&mut self) {
+    /// Resets the world for a new run, pipes are generated from `seed`
+    pub fn restart(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+        self.timer = 0.0;
+        self.last_pipe_location_index = 4;
+        self.last_gap_size = self.difficulty.at(0).gap_size;
         self.pipes.retain(|(_pipe1, _pipe2)| false);
         self.pipes.iter_mut().for_each(|(pipe1, pipe2)| {
             pipe2.restart();
             pipe1.restart();
         });
         self.base.restart();
+        self.parallax.restart();
         self.score = 0;
+
+        let level = self.difficulty.at(0);
+        self.pipe_spawn_time = level.spawn_interval;
+        self.set_velocity(level.speed);
     }
 
     pub fn player_passed_pipes(&mut self, player: &Player) -> bool {
@@ -87,36 +116,6 @@ impl World {
         });
         passed
     }
-
-    fn draw_score(&self) {
-        use macroquad::prelude::*;
-
-        // Convert score to digits
-        let score_str = self.score.to_string();
-        let digits: Vec<u32> = score_str.chars().map(|c| c.to_digit(10).unwrap()).collect();
-
-        let digit_width = self.number_texture_atlas.width;
-        let total_width = digits.len() as f32 * digit_width;
-        let screen_w = SCREEN_WIDTH;
-        let start_x = (screen_w - total_width) / 2.0;
-        let y = 50.0;
-
-        for (i, &digit) in digits.iter().enumerate() {
-            let texture = &self.number_texture_atlas.number_sprites.digits[digit as usize];
-            let x = start_x + (i as f32 * digit_width);
-
-            draw_texture_ex(
-                texture,
-                x,
-                y,
-                WHITE,
-                DrawTextureParams {
-                    dest_size: Some(vec2(digit_width, self.number_texture_atlas.height)),
-                    ..Default::default()
-                },
-            );
-        }
-    }
 }
 
 impl Node for World {
@@ -124,29 +123,28 @@ impl Node for World {
         self.timer += dt;
 
         if self.timer >= self.pipe_spawn_time {
-            // Generate next location within ±4 positions of last
-            // Removed concept of range limit - always select from all possible locations (0-9)
-            let r = gen_range(0, 10); // Generates a number from 0 to 9 inclusive
-            let location = match r {
-                0 => PipeLocation::VeryHigh,
-                1 => PipeLocation::High,
-                2 => PipeLocation::HighMid,
-                3 => PipeLocation::UpperMid,
-                4 => PipeLocation::Mid,
-                5 => PipeLocation::LowerMid,
-                6 => PipeLocation::LowMid,
-                7 => PipeLocation::Low,
-                8 => PipeLocation::VeryLow,
-                _ => PipeLocation::Lowest,
-            };
-            println!("Generated PipeLocation: {:?}", location);
+            let level = self.difficulty.at(self.score);
+            self.pipe_spawn_time = level.spawn_interval;
+            self.set_velocity(level.speed);
 
-            self.last_pipe_location_index = r;
+            // Next location must be reachable from the last one at the current speed
             let base_height = self.base.height;
+            let r = self.pipe_placer.next_location(
+                &mut self.rng,
+                self.last_pipe_location_index,
+                self.last_gap_size,
+                &level,
+                base_height,
+            );
+            let location = PipeLocation::from_index(r);
+            logging::debug!(Physics, "Generated pipe location: {:?}", location);
+
+            self.last_pipe_location_index = r;
+            self.last_gap_size = level.gap_size;
             let score= self.score;
             self.pipes.push((
-                Pipe::new(Rc::clone(&self.pipe_texture_atlas),false, self.velocity, location.clone(), base_height, score),
-                Pipe::new(Rc::clone(&self.pipe_texture_atlas), true, self.velocity, location, base_height, score)
+                Pipe::new(self.pipe_size, false, self.velocity, location.clone(), level.gap_size, base_height, score),
+                Pipe::new(self.pipe_size, true, self.velocity, location, level.gap_size, base_height, score)
             ));
             self.timer = 0.0;
         }
@@ -157,43 +155,10 @@ impl Node for World {
             pipe2.update(dt);
         });
         self.base.update(dt);
+        self.parallax.update(dt);
 
         self.pipes.retain(|(pipe1, _)| !pipe1.is_off_screen());
 
 
     }
-
-    fn draw(&mut self) {
-        let background_texture = if (self.score / 10) % 2 != 0 {
-            self.background_texture_atlas.get_texture_2d(BackgroundType::Night)
-        } else {
-            self.background_texture_atlas.get_texture_2d(BackgroundType::Day)
-        };
-
-        draw_texture_ex(
-            background_texture,
-            0.0,
-            0.0,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(vec2(SCREEN_WIDTH, SCREEN_HEIGHT)),
-                ..Default::default()
-            },
-        );
-
-
-        self.pipes.iter_mut().for_each(|(pipe1, pipe2)| {
-            pipe1.draw();
-            pipe2.draw();
-        });
-
-        self.base.draw();
-
-        // Draw score if greater than 0
-        if self.score > 0 {
-            self.draw_score();
-        }
-    }
-
-
-}
\ No newline at end of file
+}
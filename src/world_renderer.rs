@@ -0,0 +1,100 @@
+use macroquad::prelude::vec2;
+use macroquad::texture::DrawTextureParams;
+use crate::{SCREEN_WIDTH, SCREEN_HEIGHT};
+use crate::background_texture_atlas::BackgroundTextureAtlas;
+use crate::base_renderer::BaseRenderer;
+use crate::components::Renderer;
+use crate::number_texture_atlas::NumberTextureAtlas;
+use crate::pipe_renderer::PipeRenderer;
+use crate::world::World;
+
+pub struct WorldRenderer {
+    pub background_texture_atlas: BackgroundTextureAtlas,
+    pub number_texture_atlas: NumberTextureAtlas,
+    pub pipe_renderer: PipeRenderer,
+    pub base_renderer: BaseRenderer,
+    /// Keeps the background layers still
+    pub reduced_motion: bool,
+}
+
+impl WorldRenderer {
+    pub fn new(background_texture_atlas: BackgroundTextureAtlas,
+               number_texture_atlas: NumberTextureAtlas,
+               pipe_renderer: PipeRenderer,
+               base_renderer: BaseRenderer) -> Self {
+        WorldRenderer {
+            background_texture_atlas,
+            number_texture_atlas,
+            pipe_renderer,
+            base_renderer,
+            reduced_motion: false,
+        }
+    }
+
+    /// Backgrounds are stretched to the screen, each layer tiles its strip of it sideways
+    fn draw_background(&self, world: &World, alpha: f32) {
+        for (index, layer) in self.background_texture_atlas.layers.iter().enumerate() {
+            let sprite = layer.get_texture_2d(world.score);
+            let (top, bottom) = layer.rows.unwrap_or((0.0, sprite.height()));
+            let strip = sprite.rows(top, bottom);
+            let scale = SCREEN_HEIGHT / sprite.height();
+            let width = layer.tile_width();
+            let offset = if self.reduced_motion { 0.0 } else { world.parallax.offset(index, alpha) };
+
+            for x in [-offset, width - offset] {
+                strip.draw(
+                    x,
+                    top * scale,
+                    DrawTextureParams {
+                        dest_size: Some(vec2(width, (bottom - top) * scale)),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+
+    fn draw_score(&self, score: u32) {
+        // Convert score to digits
+        let score_str = score.to_string();
+        let digits: Vec<u32> = score_str.chars().map(|c| c.to_digit(10).unwrap()).collect();
+
+        let digit_width = self.number_texture_atlas.width;
+        let total_width = digits.len() as f32 * digit_width;
+        let screen_w = SCREEN_WIDTH;
+        let start_x = (screen_w - total_width) / 2.0;
+        let y = 50.0;
+
+        for (i, &digit) in digits.iter().enumerate() {
+            let texture = &self.number_texture_atlas.number_sprites.digits[digit as usize];
+            let x = start_x + (i as f32 * digit_width);
+
+            texture.draw(
+                x,
+                y,
+                DrawTextureParams {
+                    dest_size: Some(vec2(digit_width, self.number_texture_atlas.height)),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
+
+impl Renderer<World> for WorldRenderer {
+    fn draw(&mut self, world: &World, alpha: f32) {
+        self.draw_background(world, alpha);
+
+        world.pipes.iter().for_each(|(pipe1, pipe2)| {
+            self.pipe_renderer.draw(pipe1, alpha);
+            self.pipe_renderer.draw(pipe2, alpha);
+        });
+
+        self.base_renderer.draw(&world.base, alpha);
+
+        // Draw score if greater than 0
+        if world.score > 0 {
+            self.draw_score(world.score);
+        }
+    }
+}